@@ -0,0 +1,275 @@
+// Command line arguments parsing
+// When the program is started with arguments, it runs a single search and exits
+// instead of entering the interactive console loop
+
+use std::time::SystemTime;
+
+use file_searcher::{parse_time, EmptyMode, MatchTarget, OwnerFilter, SearchOptions, TimeField, TimeFilter};
+
+// Exit status when at least one object was found
+pub const EXIT_FOUND: u8 = 0;
+// Exit status when the search finished without results
+pub const EXIT_NOT_FOUND: u8 = 1;
+// Exit status for invalid command line usage
+pub const EXIT_USAGE: u8 = 2;
+// Exit status when nothing was found and some dirs (e.g. the search path) couldn't be read,
+// or when results can't be printed
+pub const EXIT_ERROR: u8 = 3;
+
+pub const USAGE: &str = "\
+Usage: file_searcher [OPTIONS] <PATH>
+
+Searches files and dirs in PATH by name and/or extension(s).
+Runs the interactive mode when started without arguments.
+
+Options:
+  -n, --name <NAME>   File name to search (without extension)
+  -m, --mode <MODE>   How the name is matched: substring (default), glob, regex or fuzzy,
+                      other modes than substring match the name with the extension,
+                      e.g. \"test_*_v?.rs\", \"^test_(a|b)\\.rs$\" or \"fsrc\"
+      --relative-path Match NAME against the path relative to PATH instead of the file name,
+                      e.g. -m glob \"src/**/mod.rs\"
+      --full-path     Match NAME against the absolute path instead of the file name,
+                      e.g. \"/fixtures/\" for everything under dirs called fixtures
+  -c, --case <CASE>   Whether letter case matters: insensitive (default), sensitive
+                      or smart (sensitive only if NAME or EXT contain uppercase letters)
+  -e, --ext <EXT>     File extension to search, can be repeated
+                      or contain several extensions separated by space
+      --invalid-utf8  Find only objects with names which are not valid UTF-8,
+                      can be used without NAME and EXT to list all of them
+  -E, --exclude <GLOB>
+                      Exclude files and dirs with names matching the glob, can be repeated
+      --exclude-regex <REGEX>
+                      Exclude files and dirs with names matching the regex, can be repeated
+      --exclude-ext <EXT>
+                      Exclude files with the extension, can be repeated
+      --exclude-dir <NAME>
+                      Don't search in dirs with the name (e.g. target, .git), can be repeated
+      --no-ignore     Don't respect .gitignore, .ignore and git excludes files
+  -H, --hidden        Search hidden files and dirs (with names starting with \".\")
+  -q, --query <QUERY> Find only objects satisfying query expression, e.g.
+                      \"name:report* and (ext:pdf or ext:docx) and not path:archive and size>1M\"
+  -g, --grep <TEXT>   Find only files containing the text, matching lines are printed
+  -G, --grep-regex <REGEX>
+                      Find only files containing text matching the regex
+  -C, --context <N>   Print N lines before and after matching lines
+      --binary        Search content of binary files too (skipped by default)
+      --encoding      Print encoding of found files: utf-8, utf-16le, utf-16be, latin-1
+                      or binary (also printed with content search)
+  -k, --kind <KIND>   Find only files with content of given kind, detected by magic numbers,
+                      can be repeated or contain several kinds separated by comma: image,
+                      audio, video, archive, elf, executable, pdf, sqlite or font
+      --mismatch      Find only files with content which disagrees with their extension,
+                      e.g. photo.txt with PNG content, the detected format is printed
+  -t, --type <TYPE>   Find only objects of given type, can be repeated or contain several
+                      types separated by comma: f (file), d (dir), l (symlink), s (socket),
+                      p (pipe), b (block device), c (char device) or x (executable file)
+  -S, --size <SIZE>   Find only files of given size, can be repeated: +100M (at least),
+                      -4k (at most), 10KiB..2GiB (range) or 512 (exactly), units are
+                      k, M, G, T, P (powers of 1000) and Ki, Mi, Gi, Ti, Pi (powers of 1024)
+      --newer-than <TIME>, --changed-within <TIME>
+                      Find only objects modified after TIME: date in UTC (2026-01-01,
+                      \"2026-01-01 12:30\"), Unix timestamp (@1767225600) or duration
+                      ago from now (2d, 6w, 1h30min), can be repeated
+      --older-than <TIME>, --changed-before <TIME>
+                      Find only objects modified before TIME, can be repeated
+      --newer <FILE>  Find only objects modified after FILE
+      --time-field <FIELD>
+                      Which time is compared by time filters: mtime (modification, default),
+                      atime (access), ctime (status change) or btime (creation)
+      --empty         Find only empty files and dirs without entries
+      --empty-recursive
+                      Find only empty files and dirs, which contain only empty files and dirs
+      --user <USER>   Find only objects owned by the user (name or uid)
+      --group <GROUP> Find only objects owned by the group (name or gid)
+      --no-user       Find only objects with owner uid which is not in /etc/passwd
+      --no-group      Find only objects with owner gid which is not in /etc/group
+      --perm <MODE>   Find only objects with given permissions, can be repeated: 0o002
+                      or o+w (all bits are set), /u+s,g+s (any bit is set) or =0o644 (exactly)
+      --min-depth <N> Don't find objects less deep than N (objects in PATH have depth 1)
+  -d, --max-depth <N> Don't find objects deeper than N and don't search in dirs at depth N
+  -j, --threads <N>   Amount of threads to search with (default: amount of CPUs)
+      --sort          Print results sorted by path
+  -h, --help          Print this help
+
+Exit status: 0 if something was found, 1 if nothing was found, 2 on invalid usage,
+3 if nothing was found and some dirs (or PATH itself) couldn't be read, or if results
+can't be printed";
+
+// Time to compare object times with: given one or the time of reference file
+enum TimeBound {
+    Time(SystemTime),
+    Reference(String),
+}
+
+// What the program should do according to the command line
+pub enum Command {
+    Interactive,
+    Help,
+    Search(Box<SearchOptions>),
+}
+
+// This function parses command line arguments (without program name)
+// Function returns error message for invalid usage
+pub fn parse_args(args: Vec<String>) -> Result<Command, String> {
+    if args.is_empty() {
+        return Ok(Command::Interactive);
+    }
+
+    let mut path = None;
+    let mut options = SearchOptions::default();
+    // Time filters are created in the end, when compared time field is known
+    let mut time_field = TimeField::default();
+    let mut time_filters = Vec::new();
+    let mut only_positional = false;
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if only_positional || !arg.starts_with('-') || arg == "-" {
+            if path.replace(arg).is_some() {
+                return Err("Only one search path can be given".to_owned());
+            }
+            continue;
+        }
+
+        // Supporting both "--name value" and "--name=value" forms
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_owned(), Some(value.to_owned())),
+            _ => (arg, None),
+        };
+
+        match flag.as_str() {
+            "--" => only_positional = true,
+            "-h" | "--help" => return Ok(Command::Help),
+            "-n" | "--name" => options = options.name(option_value(&flag, inline_value, &mut args)?),
+            "-m" | "--mode" => {
+                options = options.match_mode(option_value(&flag, inline_value, &mut args)?.parse()?)
+            }
+            "--relative-path" => options = options.match_target(MatchTarget::RelativePath),
+            "--full-path" => options = options.match_target(MatchTarget::FullPath),
+            "-c" | "--case" => {
+                options = options.case_mode(option_value(&flag, inline_value, &mut args)?.parse()?)
+            }
+            "-e" | "--ext" => {
+                let value = option_value(&flag, inline_value, &mut args)?;
+                options = options.extensions(value.split_whitespace());
+            }
+            "--invalid-utf8" => options = options.invalid_utf8(true),
+            "-E" | "--exclude" => options = options.exclude(option_value(&flag, inline_value, &mut args)?),
+            "--exclude-regex" => {
+                options = options.exclude_regex(option_value(&flag, inline_value, &mut args)?)
+            }
+            "--exclude-ext" => {
+                let value = option_value(&flag, inline_value, &mut args)?;
+                for extension in value.split_whitespace() {
+                    options = options.exclude_extension(extension);
+                }
+            }
+            "--exclude-dir" => {
+                options = options.exclude_dir(option_value(&flag, inline_value, &mut args)?)
+            }
+            "--no-ignore" => options = options.no_ignore(true),
+            "-H" | "--hidden" => options = options.hidden(true),
+            "-q" | "--query" => options = options.query(option_value(&flag, inline_value, &mut args)?),
+            "-g" | "--grep" => options = options.content(option_value(&flag, inline_value, &mut args)?),
+            "-G" | "--grep-regex" => {
+                options = options.content(option_value(&flag, inline_value, &mut args)?).content_regex(true);
+            }
+            "-C" | "--context" => {
+                let value = option_value(&flag, inline_value, &mut args)?;
+                options = options.context(parse_number(&value, "context")?);
+            }
+            "--binary" => options = options.binary(true),
+            "--encoding" => options = options.detect_encoding(true),
+            "-k" | "--kind" => {
+                let value = option_value(&flag, inline_value, &mut args)?;
+                for kind in value.split(',') {
+                    options = options.kind(kind.trim().parse()?);
+                }
+            }
+            "--mismatch" => options = options.extension_mismatch(true),
+            "-t" | "--type" => {
+                let value = option_value(&flag, inline_value, &mut args)?;
+                for file_type in value.split(',') {
+                    options = options.file_type(file_type.trim().parse()?);
+                }
+            }
+            "-S" | "--size" => {
+                let value = option_value(&flag, inline_value, &mut args)?;
+                let filter = value.parse().map_err(|err| format!("Invalid size filter: {}", err))?;
+                options = options.size(filter);
+            }
+            "--empty" => options = options.empty(EmptyMode::Empty),
+            "--empty-recursive" => options = options.empty(EmptyMode::Recursive),
+            "--user" => options = options.owner(OwnerFilter::user(&option_value(&flag, inline_value, &mut args)?)?),
+            "--group" => options = options.owner(OwnerFilter::group(&option_value(&flag, inline_value, &mut args)?)?),
+            "--no-user" => options = options.owner(OwnerFilter::NoUser),
+            "--no-group" => options = options.owner(OwnerFilter::NoGroup),
+            "--perm" => {
+                let value = option_value(&flag, inline_value, &mut args)?;
+                options = options.permission(value.parse()?);
+            }
+            "--time-field" => time_field = option_value(&flag, inline_value, &mut args)?.parse()?,
+            "--newer-than" | "--changed-within" | "--older-than" | "--changed-before" => {
+                let value = option_value(&flag, inline_value, &mut args)?;
+                let time = parse_time(&value)?;
+                let newer = flag == "--newer-than" || flag == "--changed-within";
+                time_filters.push((newer, TimeBound::Time(time)));
+            }
+            "--newer" => {
+                let reference = option_value(&flag, inline_value, &mut args)?;
+                time_filters.push((true, TimeBound::Reference(reference)));
+            }
+            "--min-depth" => {
+                let value = option_value(&flag, inline_value, &mut args)?;
+                options = options.min_depth(parse_number(&value, "depth")?);
+            }
+            "-d" | "--max-depth" => {
+                let value = option_value(&flag, inline_value, &mut args)?;
+                options = options.max_depth(parse_number(&value, "depth")?);
+            }
+            "-j" | "--threads" => {
+                let value = option_value(&flag, inline_value, &mut args)?;
+                options = options.threads(parse_number(&value, "amount of threads")?);
+            }
+            "--sort" => options = options.sorted(true),
+            _ => return Err(format!("Unknown option: {}", flag)),
+        }
+    }
+
+    // Other search options are validated when the searcher is built
+    let path = path.ok_or("The path to search must be given")?;
+
+    for (newer, bound) in time_filters {
+        let time = match bound {
+            TimeBound::Time(time) => time,
+            TimeBound::Reference(reference) => std::fs::metadata(&reference)
+                .ok()
+                .and_then(|metadata| time_field.get(&metadata))
+                .ok_or_else(|| format!("Can't get {} of reference file: {}", time_field, reference))?,
+        };
+        let filter = match newer {
+            true => TimeFilter::Newer(time_field, time),
+            false => TimeFilter::Older(time_field, time),
+        };
+        options = options.time(filter);
+    }
+
+    Ok(Command::Search(Box::new(options.root(path))))
+}
+
+// This function returns the value of an option, either given after "=" or as next argument
+fn option_value(
+    flag: &str,
+    inline_value: Option<String>,
+    args: &mut impl Iterator<Item = String>,
+) -> Result<String, String> {
+    inline_value
+        .or_else(|| args.next())
+        .ok_or_else(|| format!("Option {} requires a value", flag))
+}
+
+// This function parses non-negative number option value
+fn parse_number(value: &str, what: &str) -> Result<usize, String> {
+    value.parse().map_err(|_| format!("Invalid {}: {}", what, value))
+}
@@ -0,0 +1,229 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use regex::bytes::{Regex, RegexBuilder};
+
+use crate::encoding::{Decoder, Encoding};
+use crate::error::Error;
+use crate::options::SearchOptions;
+
+// Lines longer than this are read and matched in chunks of this size, so a file without line
+// breaks (minified code, data dumps) doesn't have to fit in memory, only the first chunk of
+// such line is kept for printing
+const MAX_LINE_LENGTH: usize = 64 * 1024;
+
+// Every chunk of a long line is matched together with this amount of bytes from the end of the
+// previous chunk, so matches shorter than that are found across chunk boundaries
+const CHUNK_OVERLAP: usize = 1024;
+
+// Line of found file content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    // Line number, counted from 1
+    pub number: u64,
+    // Line without line break, invalid UTF-8 sequences are replaced with U+FFFD
+    pub text: String,
+    // Whether the line contains searched text, other lines are context lines
+    pub is_match: bool,
+}
+
+// Searched file content, compiled from search options
+#[derive(Debug, Clone)]
+pub(crate) struct ContentMatcher {
+    regex: Regex,
+    // Amount of context lines printed before and after matching lines
+    context: usize,
+    // Whether binary files are searched too, they are searched as UTF-8
+    binary: bool,
+}
+
+impl ContentMatcher {
+    pub(crate) fn new(options: &SearchOptions, content: &str, ignore_case: bool) -> Result<Self, Error> {
+        // Literal text is searched as escaped regex, so both cases work in the same way
+        let pattern = match options.content_regex {
+            true => content.to_owned(),
+            false => regex::escape(content),
+        };
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(ignore_case)
+            .build()
+            .map_err(|err| Error::InvalidPattern { pattern: content.to_owned(), message: err.to_string() })?;
+
+        Ok(ContentMatcher { regex, context: options.context, binary: options.binary })
+    }
+
+    // This function reads file line by line and returns its encoding with matching lines and
+    // context lines, None if nothing matches, file is binary or can't be read
+    // UTF-16 and Latin-1 files are decoded to UTF-8 before matching
+    pub(crate) fn search(&self, path: &Path) -> Option<(Encoding, Vec<Line>)> {
+        let mut reader = BufReader::with_capacity(64 * 1024, File::open(path).ok()?);
+        let (encoding, bom) = Encoding::detect(reader.fill_buf().ok()?);
+        if encoding == Encoding::Binary && !self.binary {
+            return None;
+        }
+        reader.consume(bom);
+
+        let lines = match encoding {
+            Encoding::Utf16Le | Encoding::Utf16Be | Encoding::Latin1 => {
+                self.search_lines(BufReader::new(Decoder::new(reader, encoding)))
+            }
+            Encoding::Utf8 | Encoding::Binary => self.search_lines(reader),
+        };
+        lines.map(|lines| (encoding, lines))
+    }
+
+    // This function returns matching lines with context lines, None if nothing matches
+    // Only one line and context lines before it are kept in memory, so files of any size
+    // can be searched, lines are cut to MAX_LINE_LENGTH
+    fn search_lines(&self, mut reader: impl BufRead) -> Option<Vec<Line>> {
+        let mut lines = Vec::new();
+        // Last lines which are not printed yet, they are context of the next matching line
+        // Context amount is user input, so the queue grows with read lines instead of being preallocated
+        let mut before: VecDeque<(u64, Vec<u8>)> = VecDeque::new();
+        // Amount of context lines to print after the last matching line
+        let mut after = 0;
+        let mut number = 0;
+        let mut found = false;
+
+        loop {
+            let mut line = Vec::new();
+            let (read, complete) = read_chunk(&mut reader, &mut line)?;
+            if read == 0 {
+                break;
+            }
+            number += 1;
+            trim_line_break(&mut line);
+
+            let mut is_match = self.regex.is_match(&line);
+            if !complete {
+                // The rest of a long line is read even if its beginning matches
+                is_match = self.rest_of_line_matches(&mut reader, &line)? || is_match;
+            }
+            if is_match {
+                found = true;
+                lines.extend(before.drain(..).map(|(number, line)| new_line(number, &line, false)));
+                lines.push(new_line(number, &line, true));
+                after = self.context;
+            } else if after > 0 {
+                after -= 1;
+                lines.push(new_line(number, &line, false));
+            } else if self.context > 0 {
+                if before.len() == self.context {
+                    before.pop_front();
+                }
+                before.push_back((number, line));
+            }
+        }
+
+        found.then_some(lines)
+    }
+
+    // This function reads the rest of a line longer than MAX_LINE_LENGTH chunk by chunk and
+    // checks whether it matches, start is the first chunk of the line
+    fn rest_of_line_matches(&self, reader: &mut impl BufRead, start: &[u8]) -> Option<bool> {
+        let mut chunk = start[start.len() - CHUNK_OVERLAP..].to_vec();
+        let mut found = false;
+        loop {
+            let (_, complete) = read_chunk(reader, &mut chunk)?;
+            if complete {
+                trim_line_break(&mut chunk);
+            }
+            found = found || self.regex.is_match(&chunk);
+            if complete {
+                return Some(found);
+            }
+            chunk.drain(..chunk.len() - CHUNK_OVERLAP);
+        }
+    }
+}
+
+// This function appends up to MAX_LINE_LENGTH bytes of the next line to the buffer and returns
+// the amount of read bytes and whether the line ended (with a line break or the end of file)
+fn read_chunk(reader: &mut impl BufRead, buffer: &mut Vec<u8>) -> Option<(usize, bool)> {
+    let read = reader.take(MAX_LINE_LENGTH as u64).read_until(b'\n', buffer).ok()?;
+    Some((read, read < MAX_LINE_LENGTH || buffer.last() == Some(&b'\n')))
+}
+
+fn new_line(number: u64, text: &[u8], is_match: bool) -> Line {
+    Line { number, text: String::from_utf8_lossy(text).into_owned(), is_match }
+}
+
+// This function removes "\n" or "\r\n" from the end of line
+fn trim_line_break(line: &mut Vec<u8>) {
+    if line.last() == Some(&b'\n') {
+        line.pop();
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ContentMatcher, Line, CHUNK_OVERLAP, MAX_LINE_LENGTH};
+    use crate::options::SearchOptions;
+
+    // This function returns found lines as "number:text" for matching and "number-text" for context lines
+    fn search(text: &str, content: &str, context: usize) -> Option<Vec<String>> {
+        let matcher = ContentMatcher::new(&SearchOptions::new(".").context(context), content, false).unwrap();
+        let lines = matcher.search_lines(text.as_bytes())?;
+        let format = |line: Line| format!("{}{}{}", line.number, if line.is_match { ':' } else { '-' }, line.text);
+        Some(lines.into_iter().map(format).collect())
+    }
+
+    #[test]
+    fn matching_lines_without_context() {
+        assert_eq!(search("a\nfound\nb\nfound again\n", "found", 0).unwrap(), ["2:found", "4:found again"]);
+        assert_eq!(search("a\nb\n", "found", 0), None);
+        // Last line may have no line break
+        assert_eq!(search("a\nfound", "found", 0).unwrap(), ["2:found"]);
+    }
+
+    #[test]
+    fn context_lines() {
+        let text = "1\n2\nfound\n4\n5\n6\n7\nfound\n9\n";
+        assert_eq!(search(text, "found", 1).unwrap(), ["2-2", "3:found", "4-4", "7-7", "8:found", "9-9"]);
+        // Overlapping context lines are printed once
+        let context = ["1-1", "2-2", "3:found", "4-4", "5-5", "6-6", "7-7", "8:found", "9-9"];
+        assert_eq!(search(text, "found", 2).unwrap(), context[..]);
+        assert_eq!(search(text, "found", 3).unwrap(), context[..]);
+        // Matching lines in the context of other matching lines
+        assert_eq!(search("found\nfound\nx\ny\n", "found", 1).unwrap(), ["1:found", "2:found", "3-x"]);
+    }
+
+    #[test]
+    fn context_at_file_edges() {
+        assert_eq!(search("found\n2\n3\n", "found", 5).unwrap(), ["1:found", "2-2", "3-3"]);
+        assert_eq!(search("1\n2\nfound", "found", 5).unwrap(), ["1-1", "2-2", "3:found"]);
+    }
+
+    #[test]
+    fn windows_line_breaks() {
+        assert_eq!(search("a\r\nfound\r\nb\r\n", "found", 1).unwrap(), ["1-a", "2:found", "3-b"]);
+        // Line breaks are removed before matching, so "$" matches before "\r\n"
+        let options = SearchOptions::new(".").content_regex(true);
+        let matcher = ContentMatcher::new(&options, "found$", false).unwrap();
+        assert!(matcher.search_lines(&b"a\r\nfound\r\n"[..]).is_some());
+        // Lone "\r" is not a line break
+        assert_eq!(search("a\rfound\n", "found", 0).unwrap(), ["1:a\rfound"]);
+    }
+
+    #[test]
+    fn long_lines() {
+        let long = "x".repeat(3 * MAX_LINE_LENGTH);
+        // Found line is cut, following lines are still counted right
+        let text = format!("{}found{}\nfound\n", long, long);
+        let lines = search(&text, "found", 0).unwrap();
+        assert_eq!(lines[0], format!("1:{}", "x".repeat(MAX_LINE_LENGTH)));
+        assert_eq!(lines[1], "2:found");
+
+        // Match crossing the boundary of chunks
+        let text = format!("{}found", "x".repeat(MAX_LINE_LENGTH - 2));
+        assert_eq!(search(&text, "found", 0).unwrap().len(), 1);
+        let text = format!("{}found", "x".repeat(2 * MAX_LINE_LENGTH - CHUNK_OVERLAP - 2));
+        assert_eq!(search(&text, "found", 0).unwrap().len(), 1);
+        assert_eq!(search(&long, "found", 0), None);
+    }
+}
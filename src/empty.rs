@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// Which objects are considered empty by the empty filter
+// Only regular files and dirs can be empty, all entries of dirs are taken into account,
+// including hidden and ignored ones
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EmptyMode {
+    // Files of zero length and dirs without entries (default)
+    #[default]
+    Empty,
+    // Also dirs which contain only empty files and recursively empty dirs
+    Recursive,
+}
+
+impl EmptyMode {
+    // Whether given object is empty
+    pub(crate) fn is_match(self, path: &Path, metadata: &Metadata, cache: &EmptyDirs) -> bool {
+        if metadata.is_file() {
+            return metadata.len() == 0;
+        }
+        if !metadata.is_dir() {
+            return false;
+        }
+
+        match self {
+            EmptyMode::Empty => std::fs::read_dir(path).is_ok_and(|mut entries| entries.next().is_none()),
+            EmptyMode::Recursive => cache.is_recursively_empty(path),
+        }
+    }
+}
+
+// Results of recursive emptiness checks of dirs found while checking their parents
+// The walk finds a dir before its subdirs, so checking the dir reads the whole subtree and
+// the results for subdirs are kept until the walk gets to them, this way every dir is read
+// only once instead of once for every parent, which matters for deep trees of empty dirs
+// Every walk has its own cache (shared by its threads), so results of dirs the walk never
+// gets to are dropped with it and don't leak into other searches
+#[derive(Debug, Default)]
+pub(crate) struct EmptyDirs {
+    results: Mutex<HashMap<PathBuf, bool>>,
+}
+
+impl EmptyDirs {
+    // This function takes the result for given dir, every dir is checked by the walk only once
+    fn take(&self, dir: &Path) -> Option<bool> {
+        self.results.lock().unwrap().remove(dir)
+    }
+
+    // This function checks whether dir would become empty after removing empty files and dirs
+    // Subtree is read depth first with a stack of open dirs, so deep trees don't overflow the
+    // call stack, and results for all subdirs which are fully read are kept in the cache
+    // Unreadable dirs and symlinks are not empty
+    fn is_recursively_empty(&self, dir: &Path) -> bool {
+        if let Some(empty) = self.take(dir) {
+            return empty;
+        }
+
+        let mut open = match std::fs::read_dir(dir) {
+            Ok(entries) => vec![(dir.to_owned(), entries)],
+            Err(_) => return false,
+        };
+
+        while let Some((path, entries)) = open.last_mut() {
+            let entry = match entries.next() {
+                Some(Ok(entry)) => entry,
+                Some(Err(_)) => break,
+                None => {
+                    // All entries of the dir are empty, the search dir is never cached
+                    let path = std::mem::take(path);
+                    open.pop();
+                    if open.is_empty() {
+                        return true;
+                    }
+                    self.results.lock().unwrap().insert(path, true);
+                    continue;
+                }
+            };
+
+            let is_empty = match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => {
+                    let path = entry.path();
+                    match self.take(&path) {
+                        Some(empty) => empty,
+                        None => match std::fs::read_dir(&path) {
+                            Ok(entries) => {
+                                open.push((path, entries));
+                                true
+                            }
+                            Err(_) => false,
+                        },
+                    }
+                }
+                Ok(file_type) => file_type.is_file() && entry.metadata().is_ok_and(|metadata| metadata.len() == 0),
+                Err(_) => false,
+            };
+            if !is_empty {
+                break;
+            }
+        }
+
+        // Something is not empty, so all dirs which are being read are not empty either
+        let mut results = self.results.lock().unwrap();
+        for (path, _) in open.into_iter().skip(1) {
+            results.insert(path, false);
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{EmptyDirs, EmptyMode};
+    use crate::temp_dir::TempDir;
+
+    #[test]
+    fn recursively_empty_dirs() {
+        let root = TempDir::new("empty");
+        fs::create_dir_all(root.join("a/b/c")).unwrap();
+        fs::write(root.join("a/b/empty"), "").unwrap();
+        fs::create_dir_all(root.join("d/e")).unwrap();
+        fs::write(root.join("d/e/full"), "x").unwrap();
+
+        let cache = EmptyDirs::default();
+        let is_empty = |path: &str, mode: EmptyMode| {
+            let path = root.join(path);
+            mode.is_match(&path, &fs::metadata(&path).unwrap(), &cache)
+        };
+
+        assert!(is_empty("a", EmptyMode::Recursive));
+        assert!(!is_empty("a", EmptyMode::Empty));
+        // Results for subdirs are cached while checking their parent
+        assert_eq!(cache.take(&root.join("a/b")), Some(true));
+        assert!(is_empty("a/b/c", EmptyMode::Recursive));
+        assert!(is_empty("a/b/c", EmptyMode::Empty));
+        assert!(is_empty("a/b/empty", EmptyMode::Empty));
+
+        assert!(!is_empty("d", EmptyMode::Recursive));
+        assert_eq!(cache.take(&root.join("d/e")), Some(false));
+        assert!(!is_empty("d/e/full", EmptyMode::Recursive));
+    }
+}
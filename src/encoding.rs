@@ -0,0 +1,265 @@
+// Detection of text encodings and decoding of text to UTF-8
+//
+// Files are classified by their beginning:
+//     byte order mark      UTF-8, UTF-16LE or UTF-16BE
+//     NUL bytes            UTF-16 if every other byte is NUL (ASCII text without BOM),
+//                          binary otherwise
+//     valid UTF-8          UTF-8 (ASCII text too)
+//     anything else        Latin-1, which is how most of old Windows text files look
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+// Amount of bytes in the beginning of file used to detect the encoding
+const DETECT_SIZE: usize = 8 * 1024;
+
+// Text encoding of file content, or Binary for content which is not text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+    Binary,
+}
+
+impl Encoding {
+    // This function detects encoding of content by its beginning
+    // Function returns the encoding and the length of byte order mark (0 if there is none)
+    pub(crate) fn detect(start: &[u8]) -> (Encoding, usize) {
+        let start = &start[..start.len().min(DETECT_SIZE)];
+
+        if start.starts_with(&[0xEF, 0xBB, 0xBF]) {
+            return (Encoding::Utf8, 3);
+        }
+        if start.starts_with(&[0xFF, 0xFE]) {
+            return (Encoding::Utf16Le, 2);
+        }
+        if start.starts_with(&[0xFE, 0xFF]) {
+            return (Encoding::Utf16Be, 2);
+        }
+
+        if start.contains(&0) {
+            return (utf16_without_bom(start).unwrap_or(Encoding::Binary), 0);
+        }
+
+        // Detected part may end in the middle of a character
+        match std::str::from_utf8(start) {
+            Ok(_) => (Encoding::Utf8, 0),
+            Err(err) if err.error_len().is_none() => (Encoding::Utf8, 0),
+            Err(_) => (Encoding::Latin1, 0),
+        }
+    }
+
+    // This function detects encoding of file, None if it can't be read
+    pub(crate) fn of_file(path: &Path) -> Option<Encoding> {
+        let mut start = Vec::with_capacity(DETECT_SIZE);
+        File::open(path).ok()?.take(DETECT_SIZE as u64).read_to_end(&mut start).ok()?;
+        Some(Encoding::detect(&start).0)
+    }
+
+    // Whether content in this encoding is text
+    pub fn is_text(self) -> bool {
+        self != Encoding::Binary
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Encoding::Utf8 => write!(f, "utf-8"),
+            Encoding::Utf16Le => write!(f, "utf-16le"),
+            Encoding::Utf16Be => write!(f, "utf-16be"),
+            Encoding::Latin1 => write!(f, "latin-1"),
+            Encoding::Binary => write!(f, "binary"),
+        }
+    }
+}
+
+// This function recognizes UTF-16 text without byte order mark: when mostly ASCII text is
+// encoded, every other byte is NUL, which never happens in other text files
+fn utf16_without_bom(start: &[u8]) -> Option<Encoding> {
+    let pairs = start.len() / 2;
+    if pairs < 2 {
+        return None;
+    }
+
+    let zeros_at = |offset| start.chunks_exact(2).filter(|pair| pair[offset] == 0).count();
+    let (even_zeros, odd_zeros) = (zeros_at(0), zeros_at(1));
+
+    // Non-ASCII characters (e.g. Cyrillic) have no NUL bytes, so only half of the characters
+    // must be ASCII, and the other half of bytes is NUL only in rare characters like U+DE00
+    if odd_zeros * 2 >= pairs && even_zeros * 10 < odd_zeros {
+        Some(Encoding::Utf16Le)
+    } else if even_zeros * 2 >= pairs && odd_zeros * 10 < even_zeros {
+        Some(Encoding::Utf16Be)
+    } else {
+        None
+    }
+}
+
+// Reader which decodes UTF-16 or Latin-1 content to UTF-8
+// Invalid sequences, like unpaired surrogates, are replaced with U+FFFD
+pub(crate) struct Decoder<R> {
+    reader: R,
+    encoding: Encoding,
+    // Bytes read but not decoded yet: an incomplete code unit or surrogate pair
+    input: Vec<u8>,
+    // Decoded UTF-8 bytes not returned yet
+    output: VecDeque<u8>,
+}
+
+impl<R: Read> Decoder<R> {
+    pub(crate) fn new(reader: R, encoding: Encoding) -> Self {
+        Decoder { reader, encoding, input: Vec::new(), output: VecDeque::new() }
+    }
+
+    // This function decodes read input, incomplete characters are left in the input
+    // unless the end of content is reached
+    fn decode(&mut self, end: bool) {
+        let decoded = match self.encoding {
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                let mut complete = self.input.len() / 2 * 2;
+                let unit = |pair: &[u8]| match self.encoding {
+                    Encoding::Utf16Le => u16::from_le_bytes([pair[0], pair[1]]),
+                    _ => u16::from_be_bytes([pair[0], pair[1]]),
+                };
+                // High surrogate at the end waits for its pair
+                if !end && complete >= 2 && (0xD800..0xDC00).contains(&unit(&self.input[complete - 2..complete])) {
+                    complete -= 2;
+                }
+
+                let units = self.input[..complete].chunks_exact(2).map(unit);
+                let text: String = char::decode_utf16(units)
+                    .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                    .collect();
+                // Odd byte at the end of content is not a character
+                let rest = match end && complete < self.input.len() {
+                    true => "\u{FFFD}",
+                    false => "",
+                };
+                self.input.drain(..complete);
+                text + rest
+            }
+            _ => self.input.drain(..).map(char::from).collect(),
+        };
+
+        if end {
+            self.input.clear();
+        }
+        self.output.extend(decoded.into_bytes());
+    }
+}
+
+impl<R: Read> Read for Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut chunk = [0; DETECT_SIZE];
+
+        while self.output.is_empty() {
+            let read = self.reader.read(&mut chunk)?;
+            if read == 0 && self.input.is_empty() {
+                return Ok(0);
+            }
+            self.input.extend_from_slice(&chunk[..read]);
+            self.decode(read == 0);
+        }
+
+        let amount = buf.len().min(self.output.len());
+        for (byte, decoded) in buf.iter_mut().zip(self.output.drain(..amount)) {
+            *byte = decoded;
+        }
+        Ok(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reader which returns at most given amount of bytes per read, so characters are split
+    // between reads
+    struct Chunked<'a> {
+        data: &'a [u8],
+        size: usize,
+    }
+
+    impl Read for Chunked<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let amount = self.size.min(buf.len()).min(self.data.len());
+            buf[..amount].copy_from_slice(&self.data[..amount]);
+            self.data = &self.data[amount..];
+            Ok(amount)
+        }
+    }
+
+    fn decode(data: &[u8], encoding: Encoding, size: usize) -> String {
+        let mut text = String::new();
+        Decoder::new(Chunked { data, size }, encoding).read_to_string(&mut text).unwrap();
+        text
+    }
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    fn utf16be(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_be_bytes).collect()
+    }
+
+    #[test]
+    fn detection() {
+        assert_eq!(Encoding::detect(b"\xEF\xBB\xBFtext"), (Encoding::Utf8, 3));
+        assert_eq!(Encoding::detect(b"\xFF\xFEt\x00"), (Encoding::Utf16Le, 2));
+        assert_eq!(Encoding::detect(b"\xFE\xFF\x00t"), (Encoding::Utf16Be, 2));
+        assert_eq!(Encoding::detect("plain ascii, ünïcödé".as_bytes()), (Encoding::Utf8, 0));
+        assert_eq!(Encoding::detect(b""), (Encoding::Utf8, 0));
+        assert_eq!(Encoding::detect(b"caf\xE9 au lait"), (Encoding::Latin1, 0));
+        assert_eq!(Encoding::detect(&utf16le("hello world")), (Encoding::Utf16Le, 0));
+        assert_eq!(Encoding::detect(&utf16be("hello world")), (Encoding::Utf16Be, 0));
+        assert_eq!(Encoding::detect(&utf16le("привет, hello world")), (Encoding::Utf16Le, 0));
+        assert_eq!(Encoding::detect(b"\x7FELF\x02\x01\x01\x00\x00\x00\x00\x00"), (Encoding::Binary, 0));
+        // Character split at the end of the detected part is still UTF-8
+        let mut split = vec![b'a'; DETECT_SIZE - 1];
+        split.extend("é".as_bytes());
+        assert_eq!(Encoding::detect(&split), (Encoding::Utf8, 0));
+    }
+
+    #[test]
+    fn utf16_at_chunk_boundaries() {
+        let text = "a😀b\n€ x 😀😀\n";
+        for size in 1..=7 {
+            assert_eq!(decode(&utf16le(text), Encoding::Utf16Le, size), text, "chunk size {}", size);
+            assert_eq!(decode(&utf16be(text), Encoding::Utf16Be, size), text, "chunk size {}", size);
+        }
+    }
+
+    #[test]
+    fn invalid_utf16() {
+        // Unpaired high surrogate in the middle and at the end, odd byte at the end
+        let mut data = utf16le("a");
+        data.extend([0x3D, 0xD8]);
+        data.extend(utf16le("b"));
+        data.extend([0x3D, 0xD8]);
+        for size in [1, 2, 3, 64] {
+            assert_eq!(decode(&data, Encoding::Utf16Le, size), "a\u{FFFD}b\u{FFFD}");
+        }
+
+        let mut odd = utf16le("ab");
+        odd.push(b'c');
+        for size in [1, 2, 64] {
+            assert_eq!(decode(&odd, Encoding::Utf16Le, size), "ab\u{FFFD}");
+        }
+
+        // Unpaired low surrogate
+        assert_eq!(decode(&[0x00, 0xDC, b'x', 0x00], Encoding::Utf16Le, 1), "\u{FFFD}x");
+    }
+
+    #[test]
+    fn latin1() {
+        assert_eq!(decode(b"caf\xE9 \xFC\xDFer", Encoding::Latin1, 3), "café üßer");
+        assert_eq!(decode(b"", Encoding::Latin1, 3), "");
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+// Errors that can happen while building a searcher from search options
+#[derive(Debug)]
+pub enum Error {
+    // Search path was not given
+    EmptyPath,
+    // Neither a filename, extensions nor filters were given
+    NothingToSearch,
+    // Searched name is not a valid pattern for the selected match mode
+    InvalidPattern { pattern: String, message: String },
+    // Min depth is greater than max depth, so nothing could be found
+    InvalidDepth { min_depth: usize, max_depth: usize },
+    // Query expression can't be parsed, column of the problem is counted from 1
+    InvalidQuery { query: String, column: usize, message: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::EmptyPath => write!(f, "the path to search must be given"),
+            Error::NothingToSearch => write!(f, "either a filename, extensions or a filter must be given"),
+            Error::InvalidPattern { pattern, message } => {
+                write!(f, "invalid pattern \"{}\": {}", pattern, message)
+            }
+            Error::InvalidDepth { min_depth, max_depth } => {
+                write!(f, "min depth {} is greater than max depth {}", min_depth, max_depth)
+            }
+            // Query is printed on the next line with "^" under the problem
+            Error::InvalidQuery { query, column, message } => {
+                write!(f, "invalid query at column {}: {}\n{}\n{:>width$}", column, message, query, "^", width = column)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+// Error that happened while walking the search dir, for example a dir that can't be read
+#[derive(Debug)]
+pub struct WalkError {
+    // Path of the dir which caused the error
+    pub path: PathBuf,
+    pub source: io::Error,
+}
+
+impl WalkError {
+    pub(crate) fn new(path: PathBuf, source: io::Error) -> Self {
+        WalkError { path, source }
+    }
+}
+
+impl fmt::Display for WalkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.source)
+    }
+}
+
+impl std::error::Error for WalkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
@@ -0,0 +1,97 @@
+use std::path::Path;
+
+use regex::bytes::{Regex, RegexBuilder};
+
+use crate::error::Error;
+use crate::glob::Glob;
+use crate::matcher::{fold_case, has_extension, os_str_bytes};
+use crate::options::SearchOptions;
+
+// Compiled exclusions from search options
+// Excluded dirs are not only skipped in results, the search doesn't go into them at all
+// In smart case mode every exclusion decides case sensitivity by its own text, so
+// "Makefile" is case sensitive even when the searched name is not
+#[derive(Debug, Clone)]
+pub(crate) struct Excludes {
+    // Objects with names matching any of the patterns are excluded
+    globs: Vec<Folded<Glob>>,
+    regexes: Vec<Regex>,
+    // Files with any of these extensions are excluded
+    extensions: Vec<Folded<String>>,
+    // Dirs with any of these names are excluded
+    dirs: Vec<Folded<String>>,
+}
+
+// Exclusion with its case sensitivity, the exclusion is in lowercase if case is ignored
+#[derive(Debug, Clone)]
+struct Folded<T> {
+    exclusion: T,
+    ignore_case: bool,
+}
+
+impl Folded<String> {
+    fn new(text: &str, options: &SearchOptions) -> Self {
+        let ignore_case = options.case_mode.ignore_case([text]);
+        Folded { exclusion: fold_case(text, ignore_case), ignore_case }
+    }
+}
+
+impl Excludes {
+    pub(crate) fn new(options: &SearchOptions) -> Result<Self, Error> {
+        let invalid_pattern = |pattern: &str, message| Error::InvalidPattern {
+            pattern: pattern.to_owned(),
+            message,
+        };
+
+        let globs = options
+            .exclude_globs
+            .iter()
+            .map(|pattern| {
+                let Folded { exclusion, ignore_case } = Folded::new(pattern, options);
+                let glob = Glob::new(&exclusion).map_err(|message| invalid_pattern(pattern, message))?;
+                Ok(Folded { exclusion: glob, ignore_case })
+            })
+            .collect::<Result<_, _>>()?;
+        let regexes = options
+            .exclude_regexes
+            .iter()
+            .map(|pattern| {
+                RegexBuilder::new(pattern)
+                    .case_insensitive(options.case_mode.ignore_case([pattern.as_str()]))
+                    .build()
+                    .map_err(|err| invalid_pattern(pattern, err.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+        let extensions = options
+            .exclude_extensions
+            .iter()
+            .map(|ext| Folded::new(ext.trim_start_matches('.'), options))
+            .collect();
+        let dirs = options.exclude_dirs.iter().map(|dir| Folded::new(dir, options)).collect();
+
+        Ok(Excludes { globs, regexes, extensions, dirs })
+    }
+
+    // Whether given object is excluded from the search
+    pub(crate) fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        let raw_name = os_str_bytes(path.file_name(), false);
+        let lowercase_name = os_str_bytes(path.file_name(), true);
+        let name = |ignore_case| if ignore_case { &*lowercase_name } else { &*raw_name };
+
+        if is_dir && self.dirs.iter().any(|dir| dir.exclusion.as_bytes() == name(dir.ignore_case)) {
+            return true;
+        }
+        if !is_dir && self.extensions.iter().any(|ext| has_extension(name(ext.ignore_case), &ext.exclusion)) {
+            return true;
+        }
+        if self
+            .globs
+            .iter()
+            .any(|glob| glob.exclusion.is_match(&String::from_utf8_lossy(name(glob.ignore_case))))
+        {
+            return true;
+        }
+
+        self.regexes.iter().any(|regex| regex.is_match(&raw_name))
+    }
+}
@@ -0,0 +1,112 @@
+use std::fmt;
+use std::fs::Metadata;
+use std::path::Path;
+use std::str::FromStr;
+
+// Type of filesystem object, as used by type filters
+// Type of the object itself is checked, so symlinks are only of Symlink type, no matter
+// what they point to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    // Regular file ("f")
+    File,
+    // Directory ("d")
+    Dir,
+    // Symbolic link ("l")
+    Symlink,
+    // Unix domain socket ("s")
+    Socket,
+    // Named pipe ("p")
+    Fifo,
+    // Block device ("b")
+    BlockDevice,
+    // Character device ("c")
+    CharDevice,
+    // Regular file which can be executed ("x"), symlinks to such files are followed
+    Executable,
+}
+
+impl FileType {
+    // Whether object has this type
+    // Link metadata is the metadata of the object itself, metadata follows symlinks
+    pub(crate) fn is_match(self, path: &Path, metadata: Option<&Metadata>, link_metadata: Option<&Metadata>) -> bool {
+        let file_type = match (self, link_metadata) {
+            (FileType::Executable, _) => return metadata.is_some_and(|metadata| is_executable(path, metadata)),
+            (_, Some(link_metadata)) => link_metadata.file_type(),
+            (_, None) => return false,
+        };
+
+        match self {
+            FileType::File => file_type.is_file(),
+            FileType::Dir => file_type.is_dir(),
+            FileType::Symlink => file_type.is_symlink(),
+            _ => is_special(self, file_type),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_special(expected: FileType, file_type: std::fs::FileType) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    match expected {
+        FileType::Socket => file_type.is_socket(),
+        FileType::Fifo => file_type.is_fifo(),
+        FileType::BlockDevice => file_type.is_block_device(),
+        FileType::CharDevice => file_type.is_char_device(),
+        _ => false,
+    }
+}
+
+// Sockets, pipes and devices can't be found on other platforms
+#[cfg(not(unix))]
+fn is_special(_expected: FileType, _file_type: std::fs::FileType) -> bool {
+    false
+}
+
+// On Unix a file is executable if anyone has permission to execute it
+#[cfg(unix)]
+fn is_executable(_path: &Path, metadata: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+}
+
+// Other platforms have no execute permission, so executables are recognized by extension
+#[cfg(not(unix))]
+fn is_executable(path: &Path, metadata: &Metadata) -> bool {
+    let extension = path.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+    metadata.is_file() && ["exe", "com", "bat", "cmd"].contains(&extension.as_str())
+}
+
+impl FromStr for FileType {
+    type Err = String;
+
+    fn from_str(file_type: &str) -> Result<Self, Self::Err> {
+        match file_type {
+            "f" | "file" => Ok(FileType::File),
+            "d" | "dir" | "directory" => Ok(FileType::Dir),
+            "l" | "symlink" => Ok(FileType::Symlink),
+            "s" | "socket" => Ok(FileType::Socket),
+            "p" | "pipe" | "fifo" => Ok(FileType::Fifo),
+            "b" | "block-device" => Ok(FileType::BlockDevice),
+            "c" | "char-device" => Ok(FileType::CharDevice),
+            "x" | "executable" => Ok(FileType::Executable),
+            _ => Err(format!("unknown file type: {}", file_type)),
+        }
+    }
+}
+
+impl fmt::Display for FileType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileType::File => write!(f, "f"),
+            FileType::Dir => write!(f, "d"),
+            FileType::Symlink => write!(f, "l"),
+            FileType::Socket => write!(f, "s"),
+            FileType::Fifo => write!(f, "p"),
+            FileType::BlockDevice => write!(f, "b"),
+            FileType::CharDevice => write!(f, "c"),
+            FileType::Executable => write!(f, "x"),
+        }
+    }
+}
@@ -0,0 +1,152 @@
+// Fuzzy matching similar to fzf: query characters must appear in the text in the same
+// order, but not necessarily next to each other, e.g. "fsrc" matches "file_searcher.rs"
+// Matches are rated, the better the characters are placed, the higher the score
+
+use crate::matcher::fold_case;
+
+// Score for every matched character
+const SCORE_MATCH: i64 = 16;
+// Bonus for a character right after the previous matched one
+const BONUS_CONSECUTIVE: i64 = 8;
+// Bonus for a character at the beginning of a word ("s" in "file_searcher", "S" in "FileSearcher")
+const BONUS_BOUNDARY: i64 = 8;
+// Additional bonus for the first character of the text
+const BONUS_FIRST_CHAR: i64 = 8;
+// Penalty for every skipped character between matched ones
+const PENALTY_GAP: i64 = 1;
+
+// Compiled fuzzy query
+#[derive(Debug, Clone)]
+pub(crate) struct Fuzzy {
+    query: Vec<char>,
+    ignore_case: bool,
+}
+
+impl Fuzzy {
+    pub(crate) fn new(query: &str, ignore_case: bool) -> Self {
+        Fuzzy {
+            query: fold_case(query, ignore_case).chars().collect(),
+            ignore_case,
+        }
+    }
+
+    // This function returns the score of the best match of the query in given text
+    // or None if the text doesn't contain query characters in order
+    pub(crate) fn score(&self, text: &str) -> Option<i64> {
+        let original: Vec<char> = text.chars().collect();
+        let text: Vec<char> = fold_case(text, self.ignore_case).chars().collect();
+        // Lowercasing may change the amount of characters, boundaries can't be found then
+        let original = if original.len() == text.len() { &original } else { &text };
+
+        if !is_subsequence(&self.query, &text) {
+            return None;
+        }
+
+        // best[j] is the best score of matching the query so far with
+        // the last matched character at text position j
+        let mut best: Vec<Option<i64>> = vec![None; text.len()];
+
+        for (i, &query_char) in self.query.iter().enumerate() {
+            let mut next = vec![None; text.len()];
+            // The best score of previous row adjusted for the gap up to current position
+            let mut best_before: Option<i64> = None;
+
+            for j in 0..text.len() {
+                if i > 0 && j > 0 {
+                    if let Some(score) = best[j - 1] {
+                        // Score is shifted by its position, so the gap penalty for any
+                        // later match is a difference of positions
+                        let adjusted = score + PENALTY_GAP * (j as i64 - 1);
+                        best_before = Some(best_before.map_or(adjusted, |best| best.max(adjusted)));
+                    }
+                }
+
+                if text[j] != query_char {
+                    continue;
+                }
+
+                let bonus = SCORE_MATCH + boundary_bonus(original, j);
+                next[j] = if i == 0 {
+                    Some(bonus - PENALTY_GAP * j as i64)
+                } else {
+                    let after_gap = best_before.map(|score| score - PENALTY_GAP * (j as i64 - 1) + bonus);
+                    let consecutive = match j {
+                        0 => None,
+                        _ => best[j - 1].map(|score| score + bonus + BONUS_CONSECUTIVE),
+                    };
+                    after_gap.max(consecutive)
+                };
+            }
+
+            best = next;
+        }
+
+        best.into_iter().flatten().max()
+    }
+}
+
+// This function checks whether all query characters appear in the text in order
+fn is_subsequence(query: &[char], text: &[char]) -> bool {
+    let mut text = text.iter();
+    query.iter().all(|query_char| text.any(|c| c == query_char))
+}
+
+// Bonus for matching character at given position
+fn boundary_bonus(text: &[char], position: usize) -> i64 {
+    if position == 0 {
+        return BONUS_BOUNDARY + BONUS_FIRST_CHAR;
+    }
+
+    let previous = text[position - 1];
+    let current = text[position];
+
+    if !previous.is_alphanumeric() || (previous.is_lowercase() && current.is_uppercase()) {
+        BONUS_BOUNDARY
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fuzzy;
+
+    fn score(query: &str, text: &str) -> Option<i64> {
+        Fuzzy::new(query, true).score(text)
+    }
+
+    #[test]
+    fn characters_must_be_in_order() {
+        assert!(score("fsrc", "file_searcher.rs").is_some());
+        assert!(score("crsf", "file_searcher.rs").is_none());
+        assert!(score("fsx", "file_searcher.rs").is_none());
+    }
+
+    #[test]
+    fn case() {
+        assert!(score("FS", "file_searcher").is_some());
+        assert!(Fuzzy::new("FS", false).score("file_searcher").is_none());
+        assert!(Fuzzy::new("FS", false).score("FileSearcher").is_some());
+    }
+
+    #[test]
+    fn consecutive_characters_score_higher() {
+        assert!(score("main", "main.rs") > score("main", "m_a_i_n.rs"));
+        assert!(score("rs", "main.rs") > score("rs", "main.rxs"));
+    }
+
+    #[test]
+    fn word_boundaries_score_higher() {
+        assert!(score("fs", "file_searcher") > score("fs", "offset"));
+        assert!(score("fs", "FileSearcher") > score("fs", "fileseacher"));
+        // Matches closer to the beginning are better
+        assert!(score("abc", "abc_x") > score("abc", "x_abc"));
+    }
+
+    #[test]
+    fn best_placement_is_found() {
+        // The first "a" is skipped, so "ab" matches consecutively at the word boundary
+        assert_eq!(score("ab", "xa_ab"), score("ab", "xx_ab"));
+        assert!(score("ab", "xa_ab") > score("ab", "xa_b"));
+    }
+}
@@ -0,0 +1,313 @@
+// Shell-style glob patterns
+// Supported syntax:
+//     *        any amount of characters except "/"
+//     ?        any single character except "/"
+//     [a-z]    any character from the set, [!a-z] or [^a-z] for characters not in the set
+//     **       any amount of characters including "/", "**/" also matches zero dirs
+//     {a,b}    any of the alternatives, alternatives may contain other patterns
+//     \*       escaped special character
+
+// Compiled glob pattern
+#[derive(Debug, Clone)]
+pub(crate) struct Glob {
+    // Every brace expansion alternative is compiled separately
+    alternatives: Vec<Vec<Token>>,
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+    Char(char),
+    AnyChar,
+    Star,
+    // "**" followed by "/", matches nothing or any path ending with "/"
+    AnyDirs,
+    // "**" not followed by "/", matches anything
+    AnyPath,
+    Class { negated: bool, ranges: Vec<(char, char)> },
+}
+
+impl Glob {
+    // This function compiles glob pattern, returning error message for invalid pattern
+    pub(crate) fn new(pattern: &str) -> Result<Self, String> {
+        let alternatives = expand_braces(pattern)?
+            .iter()
+            .map(|pattern| tokenize(pattern))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Glob { alternatives })
+    }
+
+    // Whether given text fully matches the pattern
+    pub(crate) fn is_match(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        self.alternatives.iter().any(|tokens| matches(tokens, &text))
+    }
+}
+
+// This function expands braces, "a{b,c}d" becomes "abd" and "acd"
+// Escaped characters are kept escaped, so they are handled by tokenize() later
+fn expand_braces(pattern: &str) -> Result<Vec<String>, String> {
+    let chars: Vec<char> = pattern.chars().collect();
+
+    // Looking for the first top level brace group
+    let mut open = None;
+    let mut depth = 0;
+    let mut commas = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '[' => i = skip_class(&chars, i).ok_or("unclosed character class '['")?,
+            '{' => {
+                if depth == 0 {
+                    open = Some(i);
+                }
+                depth += 1;
+            }
+            ',' if depth == 1 => commas.push(i),
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            '}' => return Err("unmatched '}'".to_owned()),
+            _ => {}
+        }
+        i += 1;
+    }
+
+    let open = match open {
+        Some(open) if depth == 0 => open,
+        Some(_) => return Err("unclosed brace '{'".to_owned()),
+        None => return Ok(vec![pattern.to_owned()]),
+    };
+
+    let prefix: String = chars[..open].iter().collect();
+    let suffix: String = chars[i + 1..].iter().collect();
+    let bounds: Vec<usize> = std::iter::once(open).chain(commas).chain(std::iter::once(i)).collect();
+
+    let mut expanded = Vec::new();
+    for bound in bounds.windows(2) {
+        let alternative: String = chars[bound[0] + 1..bound[1]].iter().collect();
+        // Alternatives and the rest of the pattern may contain more braces
+        expanded.extend(expand_braces(&format!("{}{}{}", prefix, alternative, suffix))?);
+    }
+
+    Ok(expanded)
+}
+
+// This function returns the index of "]" closing character class started at given index
+fn skip_class(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    if matches!(chars.get(i), Some('!' | '^')) {
+        i += 1;
+    }
+    // "]" right after the opening bracket is a part of the set
+    if chars.get(i) == Some(&']') {
+        i += 1;
+    }
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            ']' => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+// This function converts pattern without braces to tokens
+fn tokenize(pattern: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                i += 1;
+                let escaped = chars.get(i).ok_or("pattern ends with escape character '\\'")?;
+                tokens.push(Token::Char(*escaped));
+            }
+            '?' => tokens.push(Token::AnyChar),
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                i += 1;
+                if chars.get(i + 1) == Some(&'/') {
+                    i += 1;
+                    tokens.push(Token::AnyDirs);
+                } else {
+                    tokens.push(Token::AnyPath);
+                }
+            }
+            '*' => tokens.push(Token::Star),
+            '[' => {
+                let end = skip_class(&chars, i).ok_or("unclosed character class '['")?;
+                tokens.push(parse_class(&chars[i + 1..end]));
+                i = end;
+            }
+            c => tokens.push(Token::Char(c)),
+        }
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+// This function parses character class contents (without brackets)
+fn parse_class(chars: &[char]) -> Token {
+    let negated = matches!(chars.first(), Some('!' | '^'));
+    let mut i = usize::from(negated);
+    let mut ranges = Vec::new();
+
+    while i < chars.len() {
+        let mut start = chars[i];
+        if start == '\\' && i + 1 < chars.len() {
+            i += 1;
+            start = chars[i];
+        }
+
+        // "a-z" range, "-" at the end of the class is a literal
+        if chars.get(i + 1) == Some(&'-') && i + 2 < chars.len() {
+            let mut end = chars[i + 2];
+            i += 2;
+            if end == '\\' && i + 1 < chars.len() {
+                i += 1;
+                end = chars[i];
+            }
+            ranges.push((start, end));
+        } else {
+            ranges.push((start, start));
+        }
+        i += 1;
+    }
+
+    Token::Class { negated, ranges }
+}
+
+// This function checks whether tokens fully match the text
+// Backtracking results are memorized, so patterns with many stars stay fast
+fn matches(tokens: &[Token], text: &[char]) -> bool {
+    let mut failed = vec![false; (tokens.len() + 1) * (text.len() + 1)];
+    matches_from(tokens, text, 0, 0, &mut failed)
+}
+
+fn matches_from(tokens: &[Token], text: &[char], token: usize, pos: usize, failed: &mut [bool]) -> bool {
+    let key = token * (text.len() + 1) + pos;
+    if failed[key] {
+        return false;
+    }
+
+    let result = match tokens.get(token) {
+        None => pos == text.len(),
+        Some(Token::Star) => (pos..=text.len())
+            .take_while(|&end| end == pos || text[end - 1] != '/')
+            .any(|end| matches_from(tokens, text, token + 1, end, failed)),
+        Some(Token::AnyPath) => {
+            (pos..=text.len()).any(|end| matches_from(tokens, text, token + 1, end, failed))
+        }
+        Some(Token::AnyDirs) => {
+            matches_from(tokens, text, token + 1, pos, failed)
+                || (pos..text.len())
+                    .filter(|&slash| text[slash] == '/')
+                    .any(|slash| matches_from(tokens, text, token + 1, slash + 1, failed))
+        }
+        Some(single) => match text.get(pos) {
+            Some(&c) if matches_char(single, c) => matches_from(tokens, text, token + 1, pos + 1, failed),
+            _ => false,
+        },
+    };
+
+    if !result {
+        failed[key] = true;
+    }
+    result
+}
+
+// This function checks whether single-character token matches given character
+fn matches_char(token: &Token, c: char) -> bool {
+    match token {
+        Token::Char(expected) => *expected == c,
+        Token::AnyChar => c != '/',
+        Token::Class { negated, ranges } => {
+            c != '/' && ranges.iter().any(|&(start, end)| start <= c && c <= end) != *negated
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Glob;
+
+    fn is_match(pattern: &str, text: &str) -> bool {
+        Glob::new(pattern).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn star_and_question_mark() {
+        assert!(is_match("*.rs", "main.rs"));
+        assert!(is_match("test_?.rs", "test_1.rs"));
+        assert!(!is_match("test_?.rs", "test_12.rs"));
+        assert!(!is_match("*.rs", "main.rs.bak"));
+        // "*" and "?" don't cross dirs
+        assert!(!is_match("*.rs", "src/main.rs"));
+        assert!(!is_match("src?main.rs", "src/main.rs"));
+    }
+
+    #[test]
+    fn any_dirs() {
+        assert!(is_match("src/**/mod.rs", "src/mod.rs"));
+        assert!(is_match("src/**/mod.rs", "src/a/b/mod.rs"));
+        assert!(!is_match("src/**/mod.rs", "src/amod.rs"));
+        assert!(is_match("**/*.rs", "main.rs"));
+        assert!(is_match("src/**", "src/a/b.rs"));
+    }
+
+    #[test]
+    fn classes() {
+        assert!(is_match("[a-c]x", "bx"));
+        assert!(!is_match("[a-c]x", "dx"));
+        assert!(is_match("[!a-c]x", "dx"));
+        assert!(is_match("[^a-c]x", "dx"));
+        assert!(!is_match("[!a-c]x", "ax"));
+        // "]" right after the opening bracket and "-" at the end are literals
+        assert!(is_match("[]]", "]"));
+        assert!(is_match("[a-]", "-"));
+        // Classes never match "/"
+        assert!(!is_match("a[!b]c", "a/c"));
+    }
+
+    #[test]
+    fn braces() {
+        assert!(is_match("*.{rs,toml}", "Cargo.toml"));
+        assert!(is_match("*.{rs,toml}", "main.rs"));
+        assert!(!is_match("*.{rs,toml}", "main.md"));
+        assert!(is_match("{a,b{c,d}}e", "bde"));
+        assert!(is_match("{x,y}{1,2}", "y1"));
+        assert!(is_match("{[ab],c}", "b"));
+    }
+
+    #[test]
+    fn escapes() {
+        assert!(is_match("\\*.rs", "*.rs"));
+        assert!(!is_match("\\*.rs", "main.rs"));
+        assert!(is_match("\\{a,b\\}", "{a,b}"));
+        assert!(is_match("[\\]]", "]"));
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        assert!(Glob::new("[abc").is_err());
+        assert!(Glob::new("{a,b").is_err());
+        assert!(Glob::new("a}").is_err());
+        assert!(Glob::new("abc\\").is_err());
+    }
+
+    #[test]
+    fn many_stars_stay_fast() {
+        let text = "a".repeat(100);
+        assert!(!is_match("*a*a*a*a*a*a*a*a*a*a*b", &text));
+    }
+}
@@ -0,0 +1,381 @@
+// Support of ignore files used in source trees: .gitignore, .ignore, .git/info/exclude
+// and the global git excludes file (core.excludesFile)
+// .gitignore files and git excludes are used only inside git repositories, .ignore files
+// are used everywhere
+//
+// Rules of a deeper dir take precedence over rules of its parents, .ignore files take
+// precedence over .gitignore files of the same dir, and the last matching rule of a file wins
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use crate::glob::Glob;
+
+// Single line of an ignore file
+#[derive(Debug)]
+struct Rule {
+    glob: Glob,
+    // "!pattern" re-includes objects excluded by previous rules
+    negated: bool,
+    // "pattern/" matches only dirs
+    dir_only: bool,
+    // Patterns with "/" in the beginning or middle are matched against the path relative
+    // to the ignore file dir, other patterns are matched against names
+    anchored: bool,
+}
+
+// Rules of one ignore file
+#[derive(Debug)]
+struct IgnoreFile {
+    // Dir the rules are applied in (in terms of walked paths)
+    base: PathBuf,
+    // Path of the base dir relative to the ignore file dir, used for ignore files found
+    // in parents of the search dir
+    prefix: String,
+    rules: Vec<Rule>,
+}
+
+impl IgnoreFile {
+    // This function reads ignore file, returning None if it doesn't exist or has no rules
+    fn read(file: &Path, base: &Path, prefix: String) -> Option<Self> {
+        let content = std::fs::read(file).ok()?;
+        let rules: Vec<Rule> = String::from_utf8_lossy(&content).lines().filter_map(parse_rule).collect();
+
+        if rules.is_empty() {
+            return None;
+        }
+
+        Some(IgnoreFile { base: base.to_owned(), prefix, rules })
+    }
+
+    // This function returns Some(true) if object is ignored, Some(false) if it is
+    // re-included by a negated rule and None if no rule matches
+    fn decide(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        let relative = path.strip_prefix(&self.base).ok()?;
+        let relative = format!("{}{}", self.prefix, slash_path(relative));
+        let name = relative.rsplit('/').next().unwrap_or_default();
+
+        self.rules
+            .iter()
+            .rev()
+            .filter(|rule| is_dir || !rule.dir_only)
+            .find(|rule| rule.glob.is_match(if rule.anchored { &relative } else { name }))
+            .map(|rule| !rule.negated)
+    }
+}
+
+// Ignore rules which apply to a dir: rules of its own ignore files and rules of its parents
+#[derive(Debug)]
+pub(crate) struct Ignore {
+    parent: Option<Arc<Ignore>>,
+    // Ignore files of one dir, from the highest precedence to the lowest one
+    files: Vec<IgnoreFile>,
+    // Whether the dir is inside git repository, so .gitignore files are used
+    in_repo: bool,
+}
+
+impl Ignore {
+    // This function collects ignore rules for the search dir, including rules from ignore
+    // files in its parents and git excludes files
+    pub(crate) fn for_root(root: &Path) -> Arc<Ignore> {
+        let mut ignore = Arc::new(Ignore { parent: None, files: Vec::new(), in_repo: false });
+
+        let absolute = match root.canonicalize() {
+            Ok(absolute) => absolute,
+            Err(_) => return Ignore::for_dir(&ignore, root),
+        };
+        let repo = absolute.ancestors().find(|dir| dir.join(".git").exists());
+
+        // Git excludes have the lowest precedence and are relative to the repository root
+        if let Some(repo) = repo {
+            let prefix = relative_prefix(&absolute, repo);
+            let files = [Some(repo.join(".git/info/exclude")), global_excludes_file()]
+                .into_iter()
+                .flatten()
+                .filter_map(|file| IgnoreFile::read(&file, root, prefix.clone()))
+                .collect();
+            ignore = Arc::new(Ignore { parent: None, files, in_repo: true });
+        }
+
+        // Parents are processed from the topmost one, so deeper dirs get higher precedence
+        let parents: Vec<&Path> = absolute.ancestors().skip(1).collect();
+        for parent in parents.into_iter().rev() {
+            let in_repo = repo.is_some_and(|repo| parent.starts_with(repo));
+            let prefix = relative_prefix(&absolute, parent);
+            let files = ignore_file_names(in_repo)
+                .filter_map(|name| IgnoreFile::read(&parent.join(name), root, prefix.clone()))
+                .collect::<Vec<_>>();
+
+            if !files.is_empty() {
+                let in_repo = ignore.in_repo;
+                ignore = Arc::new(Ignore { parent: Some(ignore), files, in_repo });
+            }
+        }
+
+        Ignore::for_dir(&ignore, root)
+    }
+
+    // This function adds rules from ignore files of given dir to rules of its parent
+    pub(crate) fn for_dir(parent: &Arc<Ignore>, dir: &Path) -> Arc<Ignore> {
+        let in_repo = parent.in_repo || dir.join(".git").exists();
+        let files: Vec<IgnoreFile> = ignore_file_names(in_repo)
+            .filter_map(|name| IgnoreFile::read(&dir.join(name), dir, String::new()))
+            .collect();
+
+        if files.is_empty() && in_repo == parent.in_repo {
+            return Arc::clone(parent);
+        }
+
+        Arc::new(Ignore { parent: Some(Arc::clone(parent)), files, in_repo })
+    }
+
+    // Whether given object is ignored according to the rules
+    pub(crate) fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut ignore = Some(self);
+
+        while let Some(current) = ignore {
+            if let Some(ignored) = current.files.iter().find_map(|file| file.decide(path, is_dir)) {
+                return ignored;
+            }
+            ignore = current.parent.as_deref();
+        }
+
+        false
+    }
+}
+
+// This function parses one line of ignore file
+fn parse_rule(line: &str) -> Option<Rule> {
+    // Trailing spaces are ignored unless they are escaped with "\"
+    let mut line = line.trim_end_matches('\r');
+    while line.ends_with(' ') && !line.ends_with("\\ ") {
+        line = &line[..line.len() - 1];
+    }
+
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let negated = line.starts_with('!');
+    if negated {
+        line = &line[1..];
+    }
+
+    let dir_only = line.ends_with('/');
+    if dir_only {
+        line = &line[..line.len() - 1];
+    }
+
+    let anchored = line.contains('/');
+    let line = line.strip_prefix('/').unwrap_or(line);
+    if line.is_empty() {
+        return None;
+    }
+
+    // Braces have no special meaning in ignore files
+    let pattern = line.replace('{', "\\{").replace('}', "\\}");
+    let glob = Glob::new(&pattern).ok()?;
+
+    Some(Rule { glob, negated, dir_only, anchored })
+}
+
+// Names of ignore files of a dir, from the highest precedence to the lowest one
+fn ignore_file_names(in_repo: bool) -> impl Iterator<Item = &'static str> {
+    [".ignore", ".gitignore"].into_iter().filter(move |&name| in_repo || name != ".gitignore")
+}
+
+// This function returns path of dir relative to its parent, ending with "/"
+fn relative_prefix(dir: &Path, parent: &Path) -> String {
+    match dir.strip_prefix(parent) {
+        Ok(relative) if !relative.as_os_str().is_empty() => format!("{}/", slash_path(relative)),
+        _ => String::new(),
+    }
+}
+
+// This function converts path to string with "/" separators, as used in ignore files and globs
+pub(crate) fn slash_path(path: &Path) -> String {
+    let mut text = String::new();
+
+    for component in path.components() {
+        if !text.is_empty() && !text.ends_with('/') {
+            text.push('/');
+        }
+        match component {
+            Component::RootDir => text.push('/'),
+            component => text.push_str(&component.as_os_str().to_string_lossy()),
+        }
+    }
+
+    text
+}
+
+// This function returns the path of global git excludes file: core.excludesFile from git
+// config or the default $XDG_CONFIG_HOME/git/ignore
+fn global_excludes_file() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| home.join(".config")));
+
+    let configs = [
+        config_home.as_ref().map(|config_home| config_home.join("git/config")),
+        home.as_ref().map(|home| home.join(".gitconfig")),
+    ];
+
+    // Values from ~/.gitconfig override values from $XDG_CONFIG_HOME/git/config
+    let configured = configs
+        .iter()
+        .rev()
+        .flatten()
+        .filter_map(|config| std::fs::read_to_string(config).ok())
+        .find_map(|config| excludes_file_setting(&config));
+
+    match configured {
+        Some(file) => match (file.strip_prefix("~/"), &home) {
+            (Some(file), Some(home)) => Some(home.join(file)),
+            _ => Some(PathBuf::from(file)),
+        },
+        None => config_home.map(|config_home| config_home.join("git/ignore")),
+    }
+}
+
+// This function finds excludesFile value in [core] section of git config
+fn excludes_file_setting(config: &str) -> Option<String> {
+    let mut in_core = false;
+    let mut value = None;
+
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_core = line.trim_start_matches('[').trim_end_matches(']').trim().eq_ignore_ascii_case("core");
+        } else if let (true, Some((key, setting))) = (in_core, line.split_once('=')) {
+            if key.trim().eq_ignore_ascii_case("excludesfile") {
+                value = Some(setting.trim().trim_matches('"').to_owned());
+            }
+        }
+    }
+
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use super::{excludes_file_setting, parse_rule, slash_path, Ignore, IgnoreFile};
+    use crate::temp_dir::TempDir;
+
+    // This function builds ignore file with given rules for the dir "/base"
+    fn ignore_file(rules: &str, prefix: &str) -> IgnoreFile {
+        let rules = rules.lines().filter_map(parse_rule).collect();
+        IgnoreFile { base: "/base".into(), prefix: prefix.to_owned(), rules }
+    }
+
+    fn decide(rules: &str, path: &str, is_dir: bool) -> Option<bool> {
+        ignore_file(rules, "").decide(&Path::new("/base").join(path), is_dir)
+    }
+
+    #[test]
+    fn rule_parsing() {
+        assert!(parse_rule("").is_none());
+        assert!(parse_rule("# comment").is_none());
+        assert!(parse_rule("   ").is_none());
+        assert!(parse_rule("/").is_none());
+
+        let rule = parse_rule("!build/\r").unwrap();
+        assert!(rule.negated && rule.dir_only && !rule.anchored);
+        let rule = parse_rule("/target").unwrap();
+        assert!(!rule.negated && !rule.dir_only && rule.anchored);
+        assert!(parse_rule("docs/*.md").unwrap().anchored);
+        // Trailing spaces are removed unless escaped
+        assert!(parse_rule("name  ").unwrap().glob.is_match("name"));
+        assert!(parse_rule("name\\ ").unwrap().glob.is_match("name "));
+        // Braces are literals
+        assert!(parse_rule("{a,b}").unwrap().glob.is_match("{a,b}"));
+        assert!(!parse_rule("{a,b}").unwrap().glob.is_match("a"));
+    }
+
+    #[test]
+    fn names_and_anchored_paths() {
+        // Rules without "/" match names at any depth
+        assert_eq!(decide("*.log", "a/b/debug.log", false), Some(true));
+        assert_eq!(decide("*.log", "debug.txt", false), None);
+        // Rules with "/" match paths relative to the ignore file dir
+        assert_eq!(decide("/target", "target", true), Some(true));
+        assert_eq!(decide("/target", "src/target", true), None);
+        assert_eq!(decide("docs/*.md", "docs/a.md", false), Some(true));
+        assert_eq!(decide("docs/*.md", "src/docs/a.md", false), None);
+        assert_eq!(decide("**/gen/*.rs", "src/gen/a.rs", false), Some(true));
+    }
+
+    #[test]
+    fn dir_only_and_negated_rules() {
+        assert_eq!(decide("build/", "build", true), Some(true));
+        assert_eq!(decide("build/", "build", false), None);
+        // The last matching rule wins
+        assert_eq!(decide("*.log\n!keep.log", "keep.log", false), Some(false));
+        assert_eq!(decide("*.log\n!keep.log", "other.log", false), Some(true));
+        assert_eq!(decide("!keep.log\n*.log", "keep.log", false), Some(true));
+    }
+
+    #[test]
+    fn prefix_of_ignore_files_above_the_search_dir() {
+        // Ignore file in the repository root, search dir is "/base" = "<root>/sub"
+        let file = ignore_file("/sub/generated\n/other", "sub/");
+        assert_eq!(file.decide(Path::new("/base/generated"), true), Some(true));
+        assert_eq!(file.decide(Path::new("/base/other"), true), None);
+        assert_eq!(file.decide(Path::new("/elsewhere/generated"), true), None);
+    }
+
+    #[test]
+    fn precedence_between_files_and_dirs() {
+        let root = TempDir::new("ignore");
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join(".gitignore"), "*.log\n*.tmp\n").unwrap();
+        // .ignore takes precedence over .gitignore of the same dir
+        fs::write(root.join(".ignore"), "!keep.log\n").unwrap();
+        // Deeper dirs take precedence over their parents
+        fs::write(root.join("sub/.gitignore"), "!*.tmp\nlocal\n").unwrap();
+
+        let ignore = Ignore::for_root(root.path());
+        let sub = Ignore::for_dir(&ignore, &root.join("sub"));
+
+        assert!(ignore.is_ignored(&root.join("debug.log"), false));
+        assert!(!ignore.is_ignored(&root.join("keep.log"), false));
+        assert!(ignore.is_ignored(&root.join("a.tmp"), false));
+        assert!(!ignore.is_ignored(&root.join("local"), false));
+        assert!(!sub.is_ignored(&root.join("sub/a.tmp"), false));
+        assert!(sub.is_ignored(&root.join("sub/a.log"), false));
+        assert!(sub.is_ignored(&root.join("sub/local"), true));
+        // Dirs without ignore files share the rules of their parent
+        assert!(Arc::ptr_eq(&Ignore::for_dir(&sub, &root.join("sub/x")), &sub));
+    }
+
+    #[test]
+    fn gitignore_only_in_repositories() {
+        let root = TempDir::new("ignore_no_repo");
+        fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        fs::write(root.join(".ignore"), "*.tmp\n").unwrap();
+
+        let ignore = Ignore::for_root(root.path());
+        assert!(!ignore.is_ignored(&root.join("debug.log"), false));
+        assert!(ignore.is_ignored(&root.join("a.tmp"), false));
+    }
+
+    #[test]
+    fn excludes_file_from_git_config() {
+        let config = "[user]\n\texcludesFile = wrong\n[core]\n\tautocrlf = false\n\texcludesFile = \"~/.gitignore_global\"\n";
+        assert_eq!(excludes_file_setting(config), Some("~/.gitignore_global".to_owned()));
+        assert_eq!(excludes_file_setting("[ Core ]\nexcludesfile=/etc/ignore"), Some("/etc/ignore".to_owned()));
+        assert_eq!(excludes_file_setting("[user]\nexcludesFile = x"), None);
+    }
+
+    #[test]
+    fn slash_paths() {
+        assert_eq!(slash_path(Path::new("a/b/c")), "a/b/c");
+        assert_eq!(slash_path(Path::new("/tmp/a")), "/tmp/a");
+        assert_eq!(slash_path(Path::new("")), "");
+    }
+}
@@ -0,0 +1,77 @@
+use std::vec;
+
+use crate::error::WalkError;
+use crate::matcher::MatchMode;
+use crate::parallel::ParallelWalk;
+use crate::searcher::{Match, Searcher};
+use crate::walk::Walk;
+
+// Lazy iterator over search results, created by Searcher::iter()
+// Directories are read only when the iterator gets to them, so taking
+// a few results or stopping early doesn't walk the whole tree
+// (except for sorted and fuzzy search, which have to find everything before sorting)
+pub struct Iter<'a> {
+    inner: Inner<'a>,
+}
+
+enum Inner<'a> {
+    Sequential(Walk<'a>),
+    Parallel(ParallelWalk),
+    Sorted(vec::IntoIter<Result<Match, WalkError>>),
+}
+
+impl<'a> Iter<'a> {
+    pub(crate) fn new(searcher: &'a Searcher) -> Self {
+        let threads = searcher.options.thread_count();
+        // When threads can't be started, the search runs in the calling thread
+        let parallel = (threads > 1).then(|| ParallelWalk::new(searcher, threads)).flatten();
+        let inner = match parallel {
+            Some(walk) => Inner::Parallel(walk),
+            None => Inner::Sequential(Walk::new(searcher)),
+        };
+
+        let mut iter = Iter { inner };
+        if searcher.options.match_mode == MatchMode::Fuzzy {
+            // The most relevant results go first, errors go before all results
+            let mut results: Vec<_> = iter.collect();
+            results.sort_by(|a, b| {
+                result_score(b).cmp(&result_score(a)).then_with(|| result_path(a).cmp(result_path(b)))
+            });
+            iter = Iter { inner: Inner::Sorted(results.into_iter()) };
+        } else if searcher.options.sorted {
+            let mut results: Vec<_> = iter.collect();
+            results.sort_by(|a, b| result_path(a).cmp(result_path(b)));
+            iter = Iter { inner: Inner::Sorted(results.into_iter()) };
+        }
+
+        iter
+    }
+}
+
+impl Iterator for Iter<'_> {
+    type Item = Result<Match, WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            Inner::Sequential(walk) => walk.next(),
+            Inner::Parallel(walk) => walk.next(),
+            Inner::Sorted(results) => results.next(),
+        }
+    }
+}
+
+// Path used to sort search results
+fn result_path(result: &Result<Match, WalkError>) -> &std::path::Path {
+    match result {
+        Ok(found) => &found.path,
+        Err(err) => &err.path,
+    }
+}
+
+// Score used to sort fuzzy search results
+fn result_score(result: &Result<Match, WalkError>) -> i64 {
+    match result {
+        Ok(found) => found.score.unwrap_or_default(),
+        Err(_) => i64::MAX,
+    }
+}
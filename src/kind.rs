@@ -0,0 +1,409 @@
+// Detection of file formats by magic numbers: known byte sequences in the beginning of files
+// Formats are detected by content only, so files with wrong extensions are detected too
+
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+// Amount of bytes in the beginning of file needed to check all signatures, PE headers
+// are usually within the first kilobyte
+const SNIFF_SIZE: usize = 1024;
+
+// Extensions which are also used for files of other formats: "nohup.out" logs, ".db" files
+// of other databases and git ".bundle" files, so files with them are never reported as
+// missing the format
+const AMBIGUOUS_EXTENSIONS: &[&str] = &["out", "db", "bundle"];
+
+// General kind of file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Image,
+    Audio,
+    Video,
+    Archive,
+    // Linux and other Unix executables and libraries
+    Elf,
+    // Windows and macOS executables and libraries
+    Executable,
+    Pdf,
+    Sqlite,
+    Font,
+}
+
+impl FromStr for Kind {
+    type Err = String;
+
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        match kind {
+            "image" => Ok(Kind::Image),
+            "audio" => Ok(Kind::Audio),
+            "video" => Ok(Kind::Video),
+            "archive" => Ok(Kind::Archive),
+            "elf" => Ok(Kind::Elf),
+            "exe" | "executable" => Ok(Kind::Executable),
+            "pdf" => Ok(Kind::Pdf),
+            "sqlite" => Ok(Kind::Sqlite),
+            "font" => Ok(Kind::Font),
+            _ => Err(format!("unknown file kind: {}", kind)),
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Image => write!(f, "image"),
+            Kind::Audio => write!(f, "audio"),
+            Kind::Video => write!(f, "video"),
+            Kind::Archive => write!(f, "archive"),
+            Kind::Elf => write!(f, "elf"),
+            Kind::Executable => write!(f, "executable"),
+            Kind::Pdf => write!(f, "pdf"),
+            Kind::Sqlite => write!(f, "sqlite"),
+            Kind::Font => write!(f, "font"),
+        }
+    }
+}
+
+// File format detected by content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Format {
+    // Short name of the format, e.g. "png" or "zip"
+    pub name: &'static str,
+    pub kind: Kind,
+    // Extensions files of this format usually have, in lowercase
+    extensions: &'static [&'static str],
+}
+
+impl Format {
+    // This function detects format of file, None if it is unknown or file can't be read
+    pub(crate) fn of_file(path: &Path) -> Option<Format> {
+        let mut start = Vec::with_capacity(SNIFF_SIZE);
+        File::open(path).ok()?.take(SNIFF_SIZE as u64).read_to_end(&mut start).ok()?;
+        Format::detect(&start)
+    }
+
+    // This function detects format by the beginning of content
+    pub(crate) fn detect(start: &[u8]) -> Option<Format> {
+        SIGNATURES
+            .iter()
+            .find(|signature| {
+                signature.parts.iter().all(|(offset, magic)| {
+                    start.get(*offset..offset + magic.len()).is_some_and(|bytes| bytes == *magic)
+                }) && (signature.is_valid)(start)
+            })
+            .map(|signature| signature.format)
+    }
+
+    // This function checks whether content of file disagrees with its extension: content
+    // has a known format which doesn't use the extension, or the extension belongs to a known
+    // format which is not detected in the content
+    // Files without extensions and with numeric ones (e.g. "libc.so.6") are never reported,
+    // neither are files with ambiguous extensions (e.g. "nohup.out") without detected format
+    pub(crate) fn is_mismatch(format: Option<Format>, path: &Path) -> bool {
+        let extension = match path.extension() {
+            Some(extension) => extension.to_string_lossy().to_lowercase(),
+            None => return false,
+        };
+        if extension.bytes().all(|c| c.is_ascii_digit()) {
+            return false;
+        }
+
+        match format {
+            Some(format) => !format.extensions.contains(&extension.as_str()),
+            None if AMBIGUOUS_EXTENSIONS.contains(&extension.as_str()) => false,
+            None => SIGNATURES
+                .iter()
+                .any(|signature| signature.format.extensions.contains(&extension.as_str())),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+// Magic number: byte sequences at given offsets, all of them must be in the content
+// Short magic numbers which also begin ordinary text ("BM", "MZ") are checked further
+struct Signature {
+    parts: &'static [(usize, &'static [u8])],
+    is_valid: fn(&[u8]) -> bool,
+    format: Format,
+}
+
+const fn signature(
+    parts: &'static [(usize, &'static [u8])],
+    name: &'static str,
+    kind: Kind,
+    extensions: &'static [&'static str],
+) -> Signature {
+    checked_signature(parts, |_| true, name, kind, extensions)
+}
+
+const fn checked_signature(
+    parts: &'static [(usize, &'static [u8])],
+    is_valid: fn(&[u8]) -> bool,
+    name: &'static str,
+    kind: Kind,
+    extensions: &'static [&'static str],
+) -> Signature {
+    Signature { parts, is_valid, format: Format { name, kind, extensions } }
+}
+
+// This function reads little-endian 32-bit number at given offset
+fn u32_at(start: &[u8], offset: usize) -> Option<u32> {
+    let bytes = start.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+// This function reads little-endian 16-bit number at given offset
+fn u16_at(start: &[u8], offset: usize) -> Option<u16> {
+    let bytes = start.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+// This function reads big-endian 16-bit number at given offset
+fn u16_be_at(start: &[u8], offset: usize) -> Option<u16> {
+    let bytes = start.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+// BMP header has reserved zero bytes and the size of the following DIB header, which is one
+// of a few known values
+fn is_bmp(start: &[u8]) -> bool {
+    let reserved_zeros = start.get(6..10).is_some_and(|reserved| reserved == [0; 4]);
+    reserved_zeros && u32_at(start, 14).is_some_and(|size| [12, 40, 52, 56, 64, 108, 124].contains(&size))
+}
+
+// ICO header has reserved zero field, type 1 (icon) or 2 (cursor) and the amount of images,
+// the first image entry has a reserved zero byte too
+fn is_ico(start: &[u8]) -> bool {
+    u16_at(start, 2).is_some_and(|kind| kind == 1 || kind == 2)
+        && u16_at(start, 4).is_some_and(|images| images > 0)
+        && start.get(9) == Some(&0)
+}
+
+// MP3 without ID3 tag starts with MPEG audio layer III frame: its header must have known
+// bitrate and sample rate, and the next frame must follow it if it is in the sniffed content
+fn is_mp3_frame(start: &[u8]) -> bool {
+    const MPEG1_BITRATES: [u32; 15] = [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320];
+    const MPEG2_BITRATES: [u32; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+    const MPEG1_SAMPLE_RATES: [u32; 3] = [44100, 48000, 32000];
+    const MPEG2_SAMPLE_RATES: [u32; 3] = [22050, 24000, 16000];
+
+    let Some(&header) = start.get(2) else {
+        return false;
+    };
+    let bitrate = (header >> 4) as usize;
+    let sample_rate = ((header >> 2) & 0b11) as usize;
+    let padding = ((header >> 1) & 1) as usize;
+    // Free format bitrate (0) is not supported by most players, 15 is invalid, sample rate 3 is reserved
+    if bitrate == 0 || bitrate == 15 || sample_rate == 3 {
+        return false;
+    }
+
+    // Second byte tells MPEG version, "\xFF\xFB" is MPEG-1 and "\xFF\xF3" is MPEG-2
+    let (bitrates, sample_rates, samples) = match start[1] {
+        0xFB => (MPEG1_BITRATES, MPEG1_SAMPLE_RATES, 144),
+        _ => (MPEG2_BITRATES, MPEG2_SAMPLE_RATES, 72),
+    };
+    let frame_length = (samples * bitrates[bitrate] * 1000 / sample_rates[sample_rate]) as usize + padding;
+    match start.get(frame_length..frame_length + 2) {
+        Some(next) => next[0] == 0xFF && next[1] & 0xE0 == 0xE0,
+        None => true,
+    }
+}
+
+// TrueType table directory has the amount of tables and values for binary search derived from
+// it: search range is 16 times the largest power of two not greater than the amount of tables
+fn is_ttf(start: &[u8]) -> bool {
+    let (Some(tables), Some(search_range), Some(entry_selector), Some(range_shift)) =
+        (u16_be_at(start, 4), u16_be_at(start, 6), u16_be_at(start, 8), u16_be_at(start, 10))
+    else {
+        return false;
+    };
+    if tables == 0 {
+        return false;
+    }
+    let (tables, search_range, range_shift) = (u32::from(tables), u32::from(search_range), u32::from(range_shift));
+    u32::from(entry_selector) == tables.ilog2()
+        && search_range == 16 << entry_selector
+        && range_shift == tables * 16 - search_range
+}
+
+// HEIF files with generic "mif1" brand contain AVIF images when "avif" is among compatible
+// brands, which follow the major brand and minor version in the ftyp box
+fn has_avif_brand(start: &[u8]) -> bool {
+    let size = start.get(..4).map_or(0, |size| u32::from_be_bytes([size[0], size[1], size[2], size[3]]) as usize);
+    start.get(16..size.min(start.len())).is_some_and(|brands| brands.chunks_exact(4).any(|brand| brand == b"avif"))
+}
+
+// DOS header of PE file points to "PE\0\0" signature at offset 0x3C
+fn is_pe(start: &[u8]) -> bool {
+    u32_at(start, 0x3C)
+        .and_then(|offset| start.get(offset as usize..offset as usize + 4))
+        .is_some_and(|signature| signature == b"PE\0\0")
+}
+
+// Known signatures, more specific ones go before more general ones with the same bytes
+const SIGNATURES: &[Signature] = &[
+    signature(&[(0, b"\x89PNG\r\n\x1a\n")], "png", Kind::Image, &["png"]),
+    signature(&[(0, b"\xFF\xD8\xFF")], "jpeg", Kind::Image, &["jpg", "jpeg", "jpe", "jfif"]),
+    signature(&[(0, b"GIF87a")], "gif", Kind::Image, &["gif"]),
+    signature(&[(0, b"GIF89a")], "gif", Kind::Image, &["gif"]),
+    signature(&[(0, b"RIFF"), (8, b"WEBP")], "webp", Kind::Image, &["webp"]),
+    signature(&[(0, b"II*\x00")], "tiff", Kind::Image, &["tif", "tiff", "dng", "nef", "cr2"]),
+    signature(&[(0, b"MM\x00*")], "tiff", Kind::Image, &["tif", "tiff", "dng", "nef", "cr2"]),
+    checked_signature(&[(0, b"BM")], is_bmp, "bmp", Kind::Image, &["bmp", "dib"]),
+    checked_signature(&[(0, b"\x00\x00")], is_ico, "ico", Kind::Image, &["ico", "cur"]),
+    signature(&[(4, b"ftypheic")], "heic", Kind::Image, &["heic", "heif"]),
+    signature(&[(4, b"ftypheix")], "heic", Kind::Image, &["heic", "heif"]),
+    signature(&[(4, b"ftypavif")], "avif", Kind::Image, &["avif"]),
+    signature(&[(4, b"ftypavis")], "avif", Kind::Image, &["avif"]),
+    checked_signature(&[(4, b"ftypmif1")], has_avif_brand, "avif", Kind::Image, &["avif"]),
+    signature(&[(4, b"ftypmif1")], "heic", Kind::Image, &["heic", "heif"]),
+    signature(&[(0, b"ID3")], "mp3", Kind::Audio, &["mp3"]),
+    checked_signature(&[(0, b"\xFF\xFB")], is_mp3_frame, "mp3", Kind::Audio, &["mp3"]),
+    checked_signature(&[(0, b"\xFF\xF3")], is_mp3_frame, "mp3", Kind::Audio, &["mp3"]),
+    signature(&[(0, b"fLaC")], "flac", Kind::Audio, &["flac"]),
+    signature(&[(0, b"OggS")], "ogg", Kind::Audio, &["ogg", "oga", "opus", "ogv"]),
+    signature(&[(0, b"RIFF"), (8, b"WAVE")], "wav", Kind::Audio, &["wav"]),
+    signature(&[(0, b"RIFF"), (8, b"AVI ")], "avi", Kind::Video, &["avi"]),
+    signature(&[(4, b"ftypM4A")], "m4a", Kind::Audio, &["m4a"]),
+    signature(&[(4, b"ftypqt")], "mov", Kind::Video, &["mov"]),
+    signature(&[(4, b"ftyp")], "mp4", Kind::Video, &["mp4", "m4v", "m4a", "mov", "3gp"]),
+    signature(&[(0, b"\x1A\x45\xDF\xA3")], "matroska", Kind::Video, &["mkv", "webm", "mka"]),
+    signature(
+        &[(0, b"PK\x03\x04")],
+        "zip",
+        Kind::Archive,
+        &["zip", "jar", "war", "apk", "whl", "epub", "docx", "xlsx", "pptx", "odt", "ods", "odp", "vsix", "nupkg"],
+    ),
+    signature(&[(0, b"PK\x05\x06")], "zip", Kind::Archive, &["zip"]),
+    signature(&[(0, b"\x1F\x8B")], "gzip", Kind::Archive, &["gz", "tgz"]),
+    signature(&[(0, b"BZh")], "bzip2", Kind::Archive, &["bz2", "tbz", "tbz2"]),
+    signature(&[(0, b"\xFD7zXZ\x00")], "xz", Kind::Archive, &["xz", "txz"]),
+    signature(&[(0, b"\x28\xB5\x2F\xFD")], "zstd", Kind::Archive, &["zst", "tzst"]),
+    signature(&[(0, b"7z\xBC\xAF\x27\x1C")], "7z", Kind::Archive, &["7z"]),
+    signature(&[(0, b"Rar!\x1A\x07")], "rar", Kind::Archive, &["rar"]),
+    signature(&[(257, b"ustar")], "tar", Kind::Archive, &["tar"]),
+    signature(&[(0, b"\x7FELF")], "elf", Kind::Elf, &["so", "o", "ko", "elf", "out", "axf"]),
+    checked_signature(
+        &[(0, b"MZ")],
+        is_pe,
+        "pe",
+        Kind::Executable,
+        &["exe", "dll", "sys", "efi", "scr", "ocx", "cpl"],
+    ),
+    signature(&[(0, b"\xFE\xED\xFA\xCE")], "mach-o", Kind::Executable, &["dylib", "bundle"]),
+    signature(&[(0, b"\xCE\xFA\xED\xFE")], "mach-o", Kind::Executable, &["dylib", "bundle"]),
+    signature(&[(0, b"\xFE\xED\xFA\xCF")], "mach-o", Kind::Executable, &["dylib", "bundle"]),
+    signature(&[(0, b"\xCF\xFA\xED\xFE")], "mach-o", Kind::Executable, &["dylib", "bundle"]),
+    signature(&[(0, b"%PDF-")], "pdf", Kind::Pdf, &["pdf"]),
+    signature(&[(0, b"SQLite format 3\x00")], "sqlite", Kind::Sqlite, &["sqlite", "sqlite3", "db", "db3"]),
+    signature(&[(0, b"wOFF")], "woff", Kind::Font, &["woff"]),
+    signature(&[(0, b"wOF2")], "woff2", Kind::Font, &["woff2"]),
+    signature(&[(0, b"OTTO")], "otf", Kind::Font, &["otf"]),
+    checked_signature(&[(0, b"\x00\x01\x00\x00\x00")], is_ttf, "ttf", Kind::Font, &["ttf"]),
+];
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{Format, Kind};
+
+    fn name(start: &[u8]) -> Option<&'static str> {
+        Format::detect(start).map(|format| format.name)
+    }
+
+    #[test]
+    fn detection() {
+        assert_eq!(name(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Some("png"));
+        assert_eq!(name(b"RIFF\0\0\0\0WEBPVP8 "), Some("webp"));
+        assert_eq!(name(b"RIFF\0\0\0\0WAVEfmt "), Some("wav"));
+        assert_eq!(name(b"\0\0\0\x20ftypM4A \0\0\0\0"), Some("m4a"));
+        assert_eq!(name(b"\0\0\0\x20ftypisom\0\0\0\0"), Some("mp4"));
+        assert_eq!(name(b"%PDF-1.7\n"), Some("pdf"));
+        assert_eq!(name(b"plain text"), None);
+        assert_eq!(Format::detect(b"\x7FELF\x02\x01").map(|format| format.kind), Some(Kind::Elf));
+    }
+
+    #[test]
+    fn short_signatures_need_valid_headers() {
+        assert_eq!(name(b"BM notes about the car\n"), None);
+        assert_eq!(name(b"MZ is a postal code prefix\n"), None);
+
+        let mut bmp = b"BM\x36\0\0\0\0\0\0\0\x36\0\0\0\x28\0\0\0".to_vec();
+        bmp.resize(54, 0);
+        assert_eq!(name(&bmp), Some("bmp"));
+
+        let mut pe = vec![0; 0x90];
+        pe[..2].copy_from_slice(b"MZ");
+        pe[0x3C] = 0x80;
+        pe[0x80..0x84].copy_from_slice(b"PE\0\0");
+        assert_eq!(name(&pe), Some("pe"));
+        pe[0x3C] = 0xFF;
+        assert_eq!(name(&pe), None);
+    }
+
+    #[test]
+    fn weak_signatures_need_valid_headers() {
+        // Icon with one image and cursor with two images, the first entry has reserved zero byte
+        assert_eq!(name(b"\0\0\x01\0\x01\0\x10\x10\0\0\x01\0\x20\0"), Some("ico"));
+        assert_eq!(name(b"\0\0\x02\0\x02\0\x20\x20\0\0\x01\0\x01\0"), Some("ico"));
+        assert_eq!(name(b"\0\0\x01\0\0\0\x10\x10\0\0"), None);
+        assert_eq!(name(b"\0\0\x03\0\x01\0\x10\x10\0\0"), None);
+        // MP4 box of 256 bytes starts with the same bytes as icons
+        assert_eq!(name(b"\0\0\x01\0ftypisom\0\0\0\0"), Some("mp4"));
+
+        // MPEG-1 layer III frame of 128 kbps at 44100 Hz is 417 bytes long
+        let mut mp3 = vec![0; 1024];
+        mp3[..3].copy_from_slice(b"\xFF\xFB\x90");
+        mp3[417..419].copy_from_slice(b"\xFF\xFB");
+        assert_eq!(name(&mp3), Some("mp3"));
+        assert_eq!(name(&mp3[..300]), Some("mp3"));
+        mp3[417] = 0;
+        assert_eq!(name(&mp3), None);
+        // Invalid bitrate and reserved sample rate
+        assert_eq!(name(b"\xFF\xFB\xF0\0"), None);
+        assert_eq!(name(b"\xFF\xF3\x9C\0"), None);
+        assert_eq!(name(b"\xFF\xFB"), None);
+
+        // TrueType font with 18 tables: search range 256, entry selector 4, range shift 32
+        assert_eq!(name(b"\0\x01\0\0\0\x12\x01\0\0\x04\0\x20"), Some("ttf"));
+        assert_eq!(name(b"\0\x01\0\0\0\x12\x01\0\0\x04\0\x21"), None);
+        assert_eq!(name(b"\0\x01\0\0\0\0\0\0\0\0\0\0"), None);
+    }
+
+    #[test]
+    fn heif_brands() {
+        assert_eq!(name(b"\0\0\0\x18ftypheix\0\0\0\0mif1heix"), Some("heic"));
+        assert_eq!(name(b"\0\0\0\x18ftypavis\0\0\0\0avifmsf1"), Some("avif"));
+        assert_eq!(name(b"\0\0\0\x18ftypmif1\0\0\0\0mif1heic"), Some("heic"));
+        assert_eq!(name(b"\0\0\0\x1Cftypmif1\0\0\0\0mif1miafavif"), Some("avif"));
+        // Brands after the ftyp box don't count
+        assert_eq!(name(b"\0\0\0\x14ftypmif1\0\0\0\0mif1avif"), Some("heic"));
+    }
+
+    #[test]
+    fn mismatches() {
+        let png = Format::detect(b"\x89PNG\r\n\x1a\n");
+        assert!(Format::is_mismatch(png, Path::new("photo.txt")));
+        assert!(!Format::is_mismatch(png, Path::new("photo.PNG")));
+        assert!(!Format::is_mismatch(png, Path::new("photo")));
+        assert!(Format::is_mismatch(None, Path::new("report.pdf")));
+        assert!(!Format::is_mismatch(None, Path::new("notes.txt")));
+        assert!(!Format::is_mismatch(None, Path::new("libc.so.6")));
+        // Ambiguous extensions are not claimed by any format
+        for path in ["nohup.out", "cache.db", "repo.bundle"] {
+            assert!(!Format::is_mismatch(None, Path::new(path)), "{}", path);
+        }
+        let elf = Format::detect(b"\x7FELF\x02\x01");
+        assert!(!Format::is_mismatch(elf, Path::new("a.out")));
+    }
+}
@@ -0,0 +1,48 @@
+// File searcher library
+// Contains the search engine used by the file_searcher program, so it can be
+// embedded into other tools
+//
+// Usage:
+//     let searcher = SearchOptions::new("/home").name("report").extension("pdf").build()?;
+//     for found in searcher.iter().filter_map(Result::ok).take(10) {
+//         println!("{}", found.path.display());
+//     }
+
+mod content;
+mod empty;
+mod encoding;
+mod error;
+mod exclude;
+mod file_type;
+mod fuzzy;
+mod glob;
+mod ignore;
+mod iter;
+mod kind;
+mod matcher;
+mod options;
+mod owner;
+mod parallel;
+mod permission;
+mod query;
+mod searcher;
+mod size;
+#[cfg(test)]
+mod temp_dir;
+mod time;
+mod walk;
+
+pub use content::Line;
+pub use empty::EmptyMode;
+pub use encoding::Encoding;
+pub use error::{Error, WalkError};
+pub use file_type::FileType;
+pub use iter::Iter;
+pub use kind::{Format, Kind};
+pub use matcher::{CaseMode, MatchMode, MatchTarget};
+pub use options::SearchOptions;
+pub use owner::OwnerFilter;
+pub use permission::{ModeMatch, PermissionFilter};
+pub use searcher::{Match, Searcher};
+pub use size::SizeFilter;
+pub use time::{parse_time, TimeField, TimeFilter};
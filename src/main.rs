@@ -1,8 +1,12 @@
-use std::ffi::OsStr;
-use std::io::{Write};
-use std::path::PathBuf;
+mod cli;
+
+use std::io::{self, Write};
+use std::process::ExitCode;
 use std::time::Instant;
 
+use cli::Command;
+use file_searcher::{Match, MatchMode, SearchOptions, Searcher};
+
 // Base to div file size in bytes for
 // If we div bytes on 1e6, we will receive file size in megabytes
 const FILE_SIZE_BASE: f64 = 1e6;
@@ -14,15 +18,19 @@ fn get_input(query: &str) -> std::io::Result<String> {
     std::io::stdout().flush()?;
 
     let mut buffer = String::new();
-    std::io::stdin().read_line(&mut buffer)?;
+    if std::io::stdin().read_line(&mut buffer)? == 0 {
+        // End of input (Ctrl+D or the end of piped input) ends the interactive mode
+        println!();
+        std::process::exit(cli::EXIT_FOUND.into());
+    }
 
     // returning String without redundant spaces and transitions to a new line
     Ok(buffer.trim().to_owned())
 }
 
-// This function gets all needed for file search data from user
-// Function also handles possible invalid input
-fn get_search_data() -> Option<(String, String, Vec<String>)> {
+// This function gets all needed for file search data from user and creates searcher
+// Function also handles possible invalid input (including invalid patterns)
+fn get_search_data() -> Option<Searcher> {
     let search_path = match get_input("Enter path to dir to search for file: ") {
         Ok(path) => path,
         Err(err) => {
@@ -37,6 +45,25 @@ fn get_search_data() -> Option<(String, String, Vec<String>)> {
             return None;
         }
     };
+    // Match mode is only needed when searching by name
+    let match_mode = if search_name.is_empty() {
+        MatchMode::default()
+    } else {
+        match get_input("Enter match mode (substring, glob, regex, fuzzy) or leave it empty for substring: ") {
+            Ok(mode) if mode.is_empty() => MatchMode::default(),
+            Ok(mode) => match mode.parse() {
+                Ok(mode) => mode,
+                Err(err) => {
+                    println!("Invalid match mode, try again: {}\n", err);
+                    return None;
+                }
+            },
+            Err(err) => {
+                println!("Error getting user input, try again: {}\n", err);
+                return None;
+            }
+        }
+    };
     let extensions = match get_input("Enter file extensions separated by space: ") {
         Ok(extensions) => get_extensions(extensions),
         Err(err) => {
@@ -45,129 +72,191 @@ fn get_search_data() -> Option<(String, String, Vec<String>)> {
         }
     };
 
+    // Hidden and ignored objects are skipped unless the user asks for them
+    let search_all = match get_input("Search hidden and ignored files too? (y/N): ") {
+        Ok(answer) => answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes"),
+        Err(err) => {
+            println!("Error getting user input, try again: {}\n", err);
+            return None;
+        }
+    };
+
     // Handling possible invalid input
     if search_path.is_empty() || (search_name.is_empty() && extensions.is_empty()) {
         println!("You must enter the path to search and either a filename or extensions");
         return None;
     }
 
-    Some((search_path.to_lowercase(), search_name.to_lowercase(), extensions))
+    let options = SearchOptions::new(search_path)
+        .name(search_name)
+        .match_mode(match_mode)
+        .extensions(extensions)
+        .hidden(search_all)
+        .no_ignore(search_all);
+
+    match options.build() {
+        Ok(searcher) => Some(searcher),
+        Err(err) => {
+            println!("Invalid search options, try again: {}\n", err);
+            None
+        }
+    }
 }
 
 // This function splits extensions list string by spaces and returns
 // vector of strings with file extensions to search later
+// Leading dots are removed, so both "rs" and ".rs" can be entered
 fn get_extensions(extensions_string: String) -> Vec<String> {
-    extensions_string.split_whitespace().map(|word| word.to_lowercase()).collect()
+    extensions_string
+        .split_whitespace()
+        .map(|word| word.trim_start_matches('.'))
+        .filter(|word| !word.is_empty())
+        .map(str::to_owned)
+        .collect()
 }
 
-// This function is needed to do converting OsStr to String more convenient
-// Also this function puts given text to lowercase
-fn os_str_to_str(os_str: Option<&OsStr>) -> String {
-    os_str
-        .unwrap_or_default()
-        .to_str()
-        .unwrap_or_default()
-        .to_lowercase()
-}
-
-// This function utilizes processes when filesystem object is found
-// Function increments objects count and calls method that prints found object info
-fn object_was_found(path: &PathBuf, now: &Instant, results_count: &mut i32) {
-    *results_count += 1;
-    print_path_info(&path, now);
-}
-
-// This function prints an information about given path (absolute path to file, file size)
+// This function prints an information about found object (absolute path to file, file size)
 // Also function prints time elapsed to find this file
-fn print_path_info(path: &PathBuf, now: &Instant) {
-    print!(
+fn print_path_info(out: &mut impl Write, found: &Match, now: &Instant) -> io::Result<()> {
+    write!(
+        out,
         "{} - Found in {} seconds",
-        path.display(),
+        found.path.display(),
         now.elapsed().as_secs_f64()
-    );
+    )?;
+
+    if let Some(size) = found.size {
+        write!(out, " - {} MB", size as f64 / FILE_SIZE_BASE)?;
+    }
+    if let Some(score) = found.score {
+        write!(out, " - score {}", score)?;
+    }
+    if let Some(encoding) = found.encoding {
+        write!(out, " - {}", encoding)?;
+    }
+    if let Some(format) = found.format {
+        write!(out, " - content: {}", format)?;
+    }
+    writeln!(out)?;
 
-    // Fetching file size
-    match std::fs::metadata(path) {
-        Ok(metadata) => {
-            print!(" - {} MB\n", metadata.len() as f64 / FILE_SIZE_BASE)
+    // Matching lines are printed as "number:line", context lines as "number-line",
+    // non-adjacent groups of lines are separated with "--"
+    let mut previous = None;
+    for line in &found.lines {
+        if previous.is_some_and(|previous| previous + 1 != line.number) {
+            writeln!(out, "    --")?;
         }
-        Err(_) => println!()
+        let separator = if line.is_match { ':' } else { '-' };
+        writeln!(out, "    {}{}{}", line.number, separator, line.text)?;
+        previous = Some(line.number);
     }
-}
 
-// This function utilizes file searching functionality
-// Function takes a path to dir where to search, filename, file extensions to search for
-// and also additional counters (time and found objects counter)
-// Function searches for needed files recursively going trough every directory
-// in given path
-// Function can search: only for filename (without extension), only for extension (or
-// several extensions), for both filename and extension(s)
-fn search_files(search_dir: &str, filename: &str, extensions: &Vec<String>,
-                now: &Instant, results_count: &mut i32) {
-    let no_extensions = extensions.is_empty();
-    let empty_filename = filename.is_empty();
-
-    // Fetching files in current dir
-    let files = match std::fs::read_dir(search_dir) {
-        Ok(files) => files,
-        Err(_) => return // Error, skip this dir
-    };
+    Ok(())
+}
 
-    for entry in files {
-        if let Ok(entry) = entry {
-            let path = entry.path();
-            let file_name = os_str_to_str(path.file_stem());
-            let file_extension = os_str_to_str(path.extension());
+// This function executes file search and prints search total results
+// Function returns found objects amount and the amount of dirs which couldn't be read,
+// or an error if results can't be printed (e.g. output is piped to "head", which exits
+// after reading enough lines)
+// Dirs which can't be read are reported to stderr and skipped
+fn run_search(searcher: &Searcher) -> io::Result<(i32, i32)> {
+    // Program counters
+    let now = Instant::now(); // Time counter
+    let mut results_count = 0; // Found objects counter
+    let mut errors_count = 0; // Unreadable dirs counter
 
-            if path.is_dir() {
-                if no_extensions && file_name.contains(filename) {
-                    // Dir matches by filename
-                    object_was_found(&path, now, results_count);
-                }
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
 
-                // Going trough this dir recursively
-                search_files(path.to_str().unwrap_or_default(), filename, extensions, now, results_count);
-            } else if empty_filename && extensions.contains(&file_extension) {
-                object_was_found(&path, now, results_count);
-            } else if path.is_file() && file_name.contains(filename) {
-                if (!no_extensions && extensions.contains(&file_extension)) || no_extensions {
-                    object_was_found(&path, now, results_count);
-                }
+    // Executing file search
+    for found in searcher.iter() {
+        match found {
+            Ok(found) => {
+                results_count += 1;
+                print_path_info(&mut out, &found, &now)?;
+            }
+            Err(err) => {
+                errors_count += 1;
+                eprintln!("Error reading {}", err);
             }
         }
     }
+
+    // Search total results (time elapsed and found results amount)
+    writeln!(
+        out,
+        "\nTotal time: {} seconds\n{} results found\n",
+        now.elapsed().as_secs_f64(),
+        results_count
+    )?;
+    out.flush()?;
+
+    Ok((results_count, errors_count))
 }
 
-// Program entry point
-fn main() {
-    // Main console program loop
+// This function handles failed printing of results
+// Closed output (broken pipe) means nobody needs more results, so the program exits quietly
+fn exit_on_output_error(err: io::Error) -> ! {
+    if err.kind() == io::ErrorKind::BrokenPipe {
+        std::process::exit(cli::EXIT_FOUND.into());
+    }
+    eprintln!("Error printing results: {}", err);
+    std::process::exit(cli::EXIT_ERROR.into())
+}
+
+// Interactive mode, main console program loop
+fn run_interactive() -> ! {
     loop {
         // Receiving needed for file search data
-        let (search_path, search_name, extensions) = match get_search_data() {
+        let searcher = match get_search_data() {
             None => continue,
-            Some(data) => data
+            Some(searcher) => searcher
         };
 
         println!();
 
-        // Program counters
-        let now = Instant::now(); // Time counter
-        let mut results_count = 0; // Found objects counter
-
-        // Executing file search
-        search_files(
-            search_path.as_str(),
-            search_name.as_str(),
-            &extensions,
-            &now,
-            &mut results_count,
-        );
-
-        // Search total results (time elapsed and found results amount)
-        println!(
-            "\nTotal time: {} seconds\n{} results found\n",
-            now.elapsed().as_secs_f64(),
-            results_count
-        );
+        if let Err(err) = run_search(&searcher) {
+            exit_on_output_error(err);
+        }
+    }
+}
+
+// Program entry point
+fn main() -> ExitCode {
+    let args = match cli::parse_args(std::env::args().skip(1).collect()) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, cli::USAGE);
+            return ExitCode::from(cli::EXIT_USAGE);
+        }
+    };
+
+    match args {
+        Command::Interactive => run_interactive(),
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            ExitCode::from(cli::EXIT_FOUND)
+        }
+        Command::Search(options) => {
+            let searcher = match (*options).build() {
+                Ok(searcher) => searcher,
+                Err(err) => {
+                    eprintln!("Invalid search options: {}", err);
+                    return ExitCode::from(cli::EXIT_USAGE);
+                }
+            };
+            let (results_count, errors_count) =
+                run_search(&searcher).unwrap_or_else(|err| exit_on_output_error(err));
+
+            // Nothing found because of errors (e.g. the search path doesn't exist) is not
+            // the same as nothing found in a readable tree
+            if results_count > 0 {
+                ExitCode::from(cli::EXIT_FOUND)
+            } else if errors_count > 0 {
+                ExitCode::from(cli::EXIT_ERROR)
+            } else {
+                ExitCode::from(cli::EXIT_NOT_FOUND)
+            }
+        }
     }
 }
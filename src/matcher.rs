@@ -0,0 +1,394 @@
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use regex::bytes::{Regex, RegexBuilder};
+
+use crate::error::Error;
+use crate::fuzzy::Fuzzy;
+use crate::glob::Glob;
+use crate::ignore::slash_path;
+use crate::options::SearchOptions;
+
+// How the searched name is compared with names of filesystem objects
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchMode {
+    // Name without extension contains the searched text (default)
+    #[default]
+    Substring,
+    // Full name (with extension) matches shell-style glob pattern, e.g. "test_*_v?.rs"
+    Glob,
+    // Full name (with extension) matches regular expression, e.g. "^test_(a|b)\.rs$"
+    Regex,
+    // Full name (with extension) contains searched characters in the same order, e.g. "fsrc"
+    // matches "file_searcher.rs", results are sorted by relevance
+    Fuzzy,
+}
+
+impl FromStr for MatchMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "substring" => Ok(MatchMode::Substring),
+            "glob" => Ok(MatchMode::Glob),
+            "regex" => Ok(MatchMode::Regex),
+            "fuzzy" => Ok(MatchMode::Fuzzy),
+            _ => Err(format!("unknown match mode: {}", mode)),
+        }
+    }
+}
+
+impl fmt::Display for MatchMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchMode::Substring => write!(f, "substring"),
+            MatchMode::Glob => write!(f, "glob"),
+            MatchMode::Regex => write!(f, "regex"),
+            MatchMode::Fuzzy => write!(f, "fuzzy"),
+        }
+    }
+}
+
+// What the searched name is compared with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchTarget {
+    // File name, without extension in substring mode (default)
+    #[default]
+    Name,
+    // Path relative to the search dir, e.g. "src/**/mod.rs" in glob mode
+    RelativePath,
+    // Absolute path (the search dir is made absolute without resolving symlinks),
+    // e.g. "/fixtures/" in substring mode
+    FullPath,
+}
+
+impl FromStr for MatchTarget {
+    type Err = String;
+
+    fn from_str(target: &str) -> Result<Self, Self::Err> {
+        match target {
+            "name" => Ok(MatchTarget::Name),
+            "relative" | "relative-path" => Ok(MatchTarget::RelativePath),
+            "full" | "full-path" => Ok(MatchTarget::FullPath),
+            _ => Err(format!("unknown match target: {}", target)),
+        }
+    }
+}
+
+impl fmt::Display for MatchTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchTarget::Name => write!(f, "name"),
+            MatchTarget::RelativePath => write!(f, "relative-path"),
+            MatchTarget::FullPath => write!(f, "full-path"),
+        }
+    }
+}
+
+// Whether letter case matters when comparing names and extensions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CaseMode {
+    // "report" matches "Report.PDF" (default)
+    #[default]
+    Insensitive,
+    // "report" doesn't match "Report.PDF"
+    Sensitive,
+    // Case sensitive only if searched name or extensions contain uppercase letters,
+    // query, content and exclusions are case sensitive only if they contain uppercase letters themselves
+    Smart,
+}
+
+impl CaseMode {
+    // This function decides whether case should be ignored for given searched texts
+    pub(crate) fn ignore_case<'a>(self, searched: impl IntoIterator<Item = &'a str>) -> bool {
+        match self {
+            CaseMode::Insensitive => true,
+            CaseMode::Sensitive => false,
+            CaseMode::Smart => !searched.into_iter().any(has_uppercase),
+        }
+    }
+}
+
+impl FromStr for CaseMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "insensitive" => Ok(CaseMode::Insensitive),
+            "sensitive" => Ok(CaseMode::Sensitive),
+            "smart" => Ok(CaseMode::Smart),
+            _ => Err(format!("unknown case mode: {}", mode)),
+        }
+    }
+}
+
+impl fmt::Display for CaseMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaseMode::Insensitive => write!(f, "insensitive"),
+            CaseMode::Sensitive => write!(f, "sensitive"),
+            CaseMode::Smart => write!(f, "smart"),
+        }
+    }
+}
+
+// This function checks whether text contains uppercase letters
+// Escaped characters are skipped, so regex classes like "\D" or "\W" are not counted
+fn has_uppercase(text: &str) -> bool {
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c.is_uppercase() {
+            return true;
+        }
+    }
+    false
+}
+
+// Searched name compiled according to the match mode
+#[derive(Debug, Clone)]
+enum Pattern {
+    Substring(Vec<u8>),
+    Glob(Glob),
+    Regex(Regex),
+    Fuzzy(Fuzzy),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct NameMatcher {
+    pattern: Pattern,
+    target: MatchTarget,
+    // Relative paths are computed by stripping the search dir from found paths
+    root: PathBuf,
+    // Search dir made absolute, full paths are computed by joining it with relative paths
+    absolute_root: PathBuf,
+    ignore_case: bool,
+    // Hidden objects the searched name refers to, e.g. ".env" or ".config/nvim"
+    hidden: HiddenNames,
+}
+
+// Hidden names which can be matched by the searched name
+#[derive(Debug, Clone)]
+enum HiddenNames {
+    // Glob, regex and fuzzy patterns refer either to all hidden names or to none of them
+    All(bool),
+    // Substring refers to hidden names starting with its parts which begin it or follow "/",
+    // parts followed by "/" must be whole names: ".config" for ".config/nvim"
+    // Each part is kept with whether it is a whole name, in lowercase if case is ignored
+    Starting(Vec<(Vec<u8>, bool)>),
+}
+
+impl NameMatcher {
+    pub(crate) fn new(options: &SearchOptions, ignore_case: bool) -> Result<Self, Error> {
+        let name = options.name.as_str();
+        let invalid_pattern = |message| Error::InvalidPattern { pattern: name.to_owned(), message };
+
+        let pattern = match options.match_mode {
+            MatchMode::Substring => Pattern::Substring(fold_case(name, ignore_case).into_bytes()),
+            MatchMode::Glob => Pattern::Glob(Glob::new(&fold_case(name, ignore_case)).map_err(invalid_pattern)?),
+            MatchMode::Regex => {
+                let regex = RegexBuilder::new(name)
+                    .case_insensitive(ignore_case)
+                    .build()
+                    .map_err(|err| invalid_pattern(err.to_string()))?;
+                Pattern::Regex(regex)
+            }
+            MatchMode::Fuzzy => Pattern::Fuzzy(Fuzzy::new(name, ignore_case)),
+        };
+
+        let root = options.root.clone();
+        let absolute_root = std::path::absolute(&root).unwrap_or_else(|_| root.clone());
+
+        let hidden = match options.match_mode {
+            MatchMode::Substring => HiddenNames::Starting(hidden_parts(&fold_case(name, ignore_case))),
+            match_mode => HiddenNames::All(refers_to_hidden(name, match_mode)),
+        };
+
+        Ok(NameMatcher { pattern, target: options.match_target, root, absolute_root, ignore_case, hidden })
+    }
+
+    // Whether hidden object with given name can be found without including hidden objects
+    // by options: the searched name refers to it
+    pub(crate) fn finds_hidden(&self, name: &OsStr) -> bool {
+        match &self.hidden {
+            HiddenNames::All(hidden) => *hidden,
+            HiddenNames::Starting(parts) => {
+                let name = os_str_bytes(Some(name), self.ignore_case);
+                parts.iter().any(|(part, whole)| if *whole { *name == **part } else { name.starts_with(part) })
+            }
+        }
+    }
+
+    // Whether hidden dir with given name is searched in when it can be found: only path
+    // patterns can match objects inside it (".config/nvim" in relative path mode),
+    // names like ".git" only list the dirs without going through their contents
+    pub(crate) fn searches_hidden_dir(&self, name: &OsStr) -> bool {
+        self.target != MatchTarget::Name && self.finds_hidden(name)
+    }
+
+    // This function returns None if the name of given object doesn't match
+    // Fuzzy matcher returns the score of the match, other matchers return 0
+    // Stem is the name without extension, in lowercase if case is ignored
+    pub(crate) fn score(&self, path: &Path, stem: &[u8]) -> Option<i64> {
+        let relative = || path.strip_prefix(&self.root).unwrap_or(path);
+        let target = match self.target {
+            MatchTarget::Name => Cow::Borrowed(Path::new(path.file_name().unwrap_or_default())),
+            MatchTarget::RelativePath => Cow::Borrowed(relative()),
+            MatchTarget::FullPath => Cow::Owned(self.absolute_root.join(relative())),
+        };
+
+        let matched = match &self.pattern {
+            Pattern::Substring(name) if self.target == MatchTarget::Name => contains_bytes(stem, name),
+            Pattern::Substring(name) => {
+                contains_bytes(&os_str_bytes(Some(target.as_os_str()), self.ignore_case), name)
+            }
+            // Glob and fuzzy matchers work with characters, so invalid UTF-8 sequences
+            // are replaced with U+FFFD and can be matched only by "?" or "*"
+            Pattern::Glob(glob) => glob.is_match(&fold_case(&slash_path(&target), self.ignore_case)),
+            Pattern::Regex(regex) => regex.is_match(&os_str_bytes(Some(target.as_os_str()), false)),
+            Pattern::Fuzzy(fuzzy) => return fuzzy.score(&slash_path(&target)),
+        };
+
+        matched.then_some(0)
+    }
+}
+
+// This function checks whether pattern refers to names starting with ".": the dot begins the
+// pattern or a path component ("/.config"), a glob alternative ("{.env,.envrc}") or, in regex
+// mode, follows the start of a name ("^\.env", "/\.config", "(^|/)\.git"), unanchored regex
+// like "\.rs$" matches dots anywhere in names, so it doesn't refer to hidden names
+fn refers_to_hidden(name: &str, match_mode: MatchMode) -> bool {
+    let (starts_name, dot, starts): (bool, &str, &[&str]) = match match_mode {
+        MatchMode::Regex => (false, "\\.", &["^", "/", "(^|/)", "(?:^|/)"]),
+        MatchMode::Glob => (true, ".", &["/", "{", ","]),
+        MatchMode::Substring | MatchMode::Fuzzy => (true, ".", &["/"]),
+    };
+
+    (starts_name && name.starts_with(dot)) || starts.iter().any(|start| name.contains(&format!("{}{}", start, dot)))
+}
+
+// This function returns components of substring which can begin hidden names: the ones which
+// start with "." and are at the start of substring or follow "/"
+fn hidden_parts(name: &str) -> Vec<(Vec<u8>, bool)> {
+    let mut parts = Vec::new();
+    let mut components = name.split('/').peekable();
+    while let Some(component) = components.next() {
+        let whole = components.peek().is_some();
+        if component.starts_with('.') {
+            parts.push((component.as_bytes().to_vec(), whole));
+        }
+    }
+    parts
+}
+
+// This function puts text to lowercase if case should be ignored
+pub(crate) fn fold_case(text: &str, ignore_case: bool) -> String {
+    if ignore_case {
+        text.to_lowercase()
+    } else {
+        text.to_owned()
+    }
+}
+
+// This function returns raw bytes of OS string, on Unix names may be not valid UTF-8
+// Also this function puts given text to lowercase if case should be ignored, names which are
+// not valid UTF-8 are put to lowercase only for ASCII letters
+pub(crate) fn os_str_bytes(os_str: Option<&OsStr>, ignore_case: bool) -> Cow<'_, [u8]> {
+    let os_str = os_str.unwrap_or_default();
+
+    if ignore_case {
+        return match os_str.to_str() {
+            Some(text) => Cow::Owned(text.to_lowercase().into_bytes()),
+            None => Cow::Owned(raw_bytes(os_str).to_ascii_lowercase()),
+        };
+    }
+
+    raw_bytes(os_str)
+}
+
+#[cfg(unix)]
+fn raw_bytes(os_str: &OsStr) -> Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    Cow::Borrowed(os_str.as_bytes())
+}
+
+// Other platforms don't give access to raw bytes, so names are converted lossy
+#[cfg(not(unix))]
+fn raw_bytes(os_str: &OsStr) -> Cow<'_, [u8]> {
+    match os_str.to_string_lossy() {
+        Cow::Borrowed(text) => Cow::Borrowed(text.as_bytes()),
+        Cow::Owned(text) => Cow::Owned(text.into_bytes()),
+    }
+}
+
+// This function checks whether file name ends with given extension
+// Extension may consist of several parts, e.g. "tar.gz" or "d.ts"
+pub(crate) fn has_extension(file_name: &[u8], extension: &str) -> bool {
+    let extension = extension.as_bytes();
+    // Name must have non-empty stem, ".gz" file has no extension
+    file_name.len() > extension.len() + 1
+        && file_name.ends_with(extension)
+        && file_name[file_name.len() - extension.len() - 1] == b'.'
+}
+
+// This function checks whether bytes contain given bytes sequence
+fn contains_bytes(bytes: &[u8], searched: &[u8]) -> bool {
+    searched.is_empty() || bytes.windows(searched.len()).any(|window| window == searched)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+
+    use super::{MatchMode, MatchTarget, NameMatcher};
+    use crate::options::SearchOptions;
+
+    fn matcher(name: &str, match_mode: MatchMode, match_target: MatchTarget) -> NameMatcher {
+        let options = SearchOptions::new(".").name(name).match_mode(match_mode).match_target(match_target);
+        NameMatcher::new(&options, false).unwrap()
+    }
+
+    fn finds_hidden(name: &str, match_mode: MatchMode, hidden_name: &str) -> bool {
+        matcher(name, match_mode, MatchTarget::Name).finds_hidden(OsStr::new(hidden_name))
+    }
+
+    #[test]
+    fn hidden_names_in_regex_mode() {
+        assert!(finds_hidden("^\\.env", MatchMode::Regex, ".env"));
+        assert!(finds_hidden("(^|/)\\.git$", MatchMode::Regex, ".git"));
+        assert!(finds_hidden("src/\\.secret", MatchMode::Regex, ".secret.rs"));
+        // Dots which don't start names
+        assert!(!finds_hidden("\\.rs$", MatchMode::Regex, ".secret.rs"));
+        assert!(!finds_hidden("(min|map)\\.js", MatchMode::Regex, ".hidden.min.js"));
+        assert!(!finds_hidden("\\.env", MatchMode::Regex, ".env"));
+    }
+
+    #[test]
+    fn hidden_names_in_substring_mode() {
+        assert!(finds_hidden(".env", MatchMode::Substring, ".env"));
+        assert!(finds_hidden(".env", MatchMode::Substring, ".envrc"));
+        assert!(!finds_hidden(".min", MatchMode::Substring, ".hidden.min.js"));
+        assert!(!finds_hidden("env", MatchMode::Substring, ".env"));
+
+        let path = matcher("src/.config/nvim", MatchMode::Substring, MatchTarget::RelativePath);
+        assert!(path.finds_hidden(OsStr::new(".config")));
+        assert!(path.searches_hidden_dir(OsStr::new(".config")));
+        assert!(!path.finds_hidden(OsStr::new(".configs")));
+        assert!(!path.finds_hidden(OsStr::new(".git")));
+
+        // Hidden dirs found by name are not searched in
+        let name = matcher(".git", MatchMode::Substring, MatchTarget::Name);
+        assert!(name.finds_hidden(OsStr::new(".git")));
+        assert!(!name.searches_hidden_dir(OsStr::new(".git")));
+    }
+
+    #[test]
+    fn hidden_names_ignore_case() {
+        let matcher = NameMatcher::new(&SearchOptions::new(".").name(".ENV"), true).unwrap();
+        assert!(matcher.finds_hidden(OsStr::new(".Env.local")));
+    }
+}
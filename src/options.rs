@@ -0,0 +1,363 @@
+use std::path::PathBuf;
+
+use crate::content::ContentMatcher;
+use crate::empty::EmptyMode;
+use crate::error::Error;
+use crate::exclude::Excludes;
+use crate::kind::Kind;
+use crate::file_type::FileType;
+use crate::matcher::{fold_case, CaseMode, MatchMode, MatchTarget, NameMatcher};
+use crate::owner::{Accounts, OwnerFilter};
+use crate::permission::PermissionFilter;
+use crate::query::Query;
+use crate::searcher::Searcher;
+use crate::size::SizeFilter;
+use crate::time::TimeFilter;
+
+// Search parameters builder
+// Every setter consumes options and returns them back, so calls can be chained
+// Searcher is created from options with build(), which also validates them
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    pub(crate) root: PathBuf,
+    pub(crate) name: String,
+    pub(crate) match_mode: MatchMode,
+    pub(crate) match_target: MatchTarget,
+    pub(crate) case_mode: CaseMode,
+    pub(crate) extensions: Vec<String>,
+    pub(crate) invalid_utf8: bool,
+    pub(crate) exclude_globs: Vec<String>,
+    pub(crate) exclude_regexes: Vec<String>,
+    pub(crate) exclude_extensions: Vec<String>,
+    pub(crate) exclude_dirs: Vec<String>,
+    pub(crate) no_ignore: bool,
+    pub(crate) hidden: bool,
+    pub(crate) min_depth: usize,
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) file_types: Vec<FileType>,
+    pub(crate) size_filters: Vec<SizeFilter>,
+    pub(crate) time_filters: Vec<TimeFilter>,
+    pub(crate) empty: Option<EmptyMode>,
+    pub(crate) query: Option<String>,
+    pub(crate) content: Option<String>,
+    pub(crate) content_regex: bool,
+    pub(crate) context: usize,
+    pub(crate) binary: bool,
+    pub(crate) detect_encoding: bool,
+    pub(crate) kinds: Vec<Kind>,
+    pub(crate) extension_mismatch: bool,
+    pub(crate) owner_filters: Vec<OwnerFilter>,
+    pub(crate) permission_filters: Vec<PermissionFilter>,
+    pub(crate) threads: usize,
+    pub(crate) sorted: bool,
+}
+
+impl SearchOptions {
+    // Creates options to search in given dir
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        SearchOptions {
+            root: root.into(),
+            ..Default::default()
+        }
+    }
+
+    // Changes dir to search in
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = root.into();
+        self
+    }
+
+    // File name to search, how it is compared with names depends on match mode
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    // How the name is matched, see MatchMode
+    pub fn match_mode(mut self, match_mode: MatchMode) -> Self {
+        self.match_mode = match_mode;
+        self
+    }
+
+    // What the name is matched against: file name, relative or full path, see MatchTarget
+    // Works in all match modes, extensions are still compared with the file name
+    pub fn match_target(mut self, match_target: MatchTarget) -> Self {
+        self.match_target = match_target;
+        self
+    }
+
+    // Whether letter case matters for names and extensions, see CaseMode
+    pub fn case_mode(mut self, case_mode: CaseMode) -> Self {
+        self.case_mode = case_mode;
+        self
+    }
+
+    // Adds one file extension to search for
+    // Extension may consist of several parts, e.g. "tar.gz", leading dot is allowed
+    pub fn extension(mut self, extension: impl Into<String>) -> Self {
+        self.extensions.push(extension.into());
+        self
+    }
+
+    // Adds several file extensions to search for
+    pub fn extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.extensions.extend(extensions.into_iter().map(Into::into));
+        self
+    }
+
+    // Whether only objects with names which are not valid UTF-8 should be found
+    // Can be used without name and extensions to list all such objects
+    pub fn invalid_utf8(mut self, invalid_utf8: bool) -> Self {
+        self.invalid_utf8 = invalid_utf8;
+        self
+    }
+
+    // Excludes files and dirs with names matching given glob pattern, e.g. "*.bak"
+    // Excluded dirs are not searched in
+    pub fn exclude(mut self, glob: impl Into<String>) -> Self {
+        self.exclude_globs.push(glob.into());
+        self
+    }
+
+    // Excludes files and dirs with names matching given regex
+    // Excluded dirs are not searched in
+    pub fn exclude_regex(mut self, regex: impl Into<String>) -> Self {
+        self.exclude_regexes.push(regex.into());
+        self
+    }
+
+    // Excludes files with given extension, e.g. "min.js"
+    pub fn exclude_extension(mut self, extension: impl Into<String>) -> Self {
+        self.exclude_extensions.push(extension.into());
+        self
+    }
+
+    // Excludes dirs with given name, e.g. "target" or ".git", they are not searched in
+    pub fn exclude_dir(mut self, name: impl Into<String>) -> Self {
+        self.exclude_dirs.push(name.into());
+        self
+    }
+
+    // Whether ignore files should not be used
+    // By default files and dirs ignored by .gitignore (inside git repositories), .ignore,
+    // .git/info/exclude and global git excludes file are not found and not searched in,
+    // .git dirs are not searched in either
+    pub fn no_ignore(mut self, no_ignore: bool) -> Self {
+        self.no_ignore = no_ignore;
+        self
+    }
+
+    // Whether hidden files and dirs (with names starting with ".") are searched
+    // Hidden objects are skipped by default, though when searched name refers to them
+    // (".env" finds ".envrc", "^\.env" in regex mode) they can be found, hidden dirs are searched in
+    // only when paths are matched (".config/nvim" with MatchTarget::RelativePath)
+    // Search dir itself is searched even if it is hidden
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    // Objects less deep than given depth are not found, objects in the search dir have depth 1
+    // Dirs are still searched in, so deeper objects can be found
+    pub fn min_depth(mut self, min_depth: usize) -> Self {
+        self.min_depth = min_depth;
+        self
+    }
+
+    // Objects deeper than given depth are not found, objects in the search dir have depth 1
+    // Dirs at max depth are not searched in at all
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    // Adds type of objects to find, e.g. FileType::Symlink, objects of any of given types are found
+    pub fn file_type(mut self, file_type: FileType) -> Self {
+        self.file_types.push(file_type);
+        self
+    }
+
+    // Adds file size filter, e.g. "+100M".parse()? for files of at least 100 megabytes
+    // Only files are found when size filters are given, all of the filters must be satisfied
+    pub fn size(mut self, filter: SizeFilter) -> Self {
+        self.size_filters.push(filter);
+        self
+    }
+
+    // Adds filter by modification, access, change or creation time, all of the filters
+    // must be satisfied, e.g. TimeFilter::Newer(TimeField::Modified, parse_time("2d")?)
+    // for objects modified during last two days
+    pub fn time(mut self, filter: TimeFilter) -> Self {
+        self.time_filters.push(filter);
+        self
+    }
+
+    // Finds only empty files and dirs, see EmptyMode
+    pub fn empty(mut self, empty: EmptyMode) -> Self {
+        self.empty = Some(empty);
+        self
+    }
+
+    // Query expression objects must satisfy, e.g. "name:report* and (ext:pdf or ext:docx)"
+    // Query is combined with the name, extensions and filters, see the query module for syntax
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.query = Some(query.into());
+        self
+    }
+
+    // Text files must contain, found files get matching lines in Match::lines
+    // Files are read line by line, so big files don't need much memory
+    pub fn content(mut self, content: impl Into<String>) -> Self {
+        self.content = Some(content.into());
+        self
+    }
+
+    // Whether searched content is a regular expression instead of literal text
+    pub fn content_regex(mut self, content_regex: bool) -> Self {
+        self.content_regex = content_regex;
+        self
+    }
+
+    // Amount of lines before and after matching lines added to Match::lines as context
+    pub fn context(mut self, context: usize) -> Self {
+        self.context = context;
+        self
+    }
+
+    // Whether content of binary files (with NUL bytes in the beginning) is searched too
+    // Binary files are skipped by default
+    pub fn binary(mut self, binary: bool) -> Self {
+        self.binary = binary;
+        self
+    }
+
+    // Whether found files are classified as text (with encoding) or binary, see Match::encoding
+    // Beginning of every found file is read then
+    pub fn detect_encoding(mut self, detect_encoding: bool) -> Self {
+        self.detect_encoding = detect_encoding;
+        self
+    }
+
+    // Adds kind of file content to find, e.g. Kind::Image, files of any of given kinds are found
+    // Kinds are detected by magic numbers in the beginning of files, not by extensions
+    pub fn kind(mut self, kind: Kind) -> Self {
+        self.kinds.push(kind);
+        self
+    }
+
+    // Whether only files with content which disagrees with their extension should be found,
+    // e.g. "photo.txt" with PNG content or "report.pdf" without PDF content
+    // Detected format is available in Match::format
+    pub fn extension_mismatch(mut self, extension_mismatch: bool) -> Self {
+        self.extension_mismatch = extension_mismatch;
+        self
+    }
+
+    // Adds filter by owner, e.g. OwnerFilter::user("alice")? or OwnerFilter::NoUser for
+    // objects of deleted users, all of the filters must be satisfied (Unix only)
+    pub fn owner(mut self, filter: OwnerFilter) -> Self {
+        self.owner_filters.push(filter);
+        self
+    }
+
+    // Adds filter by permission bits, e.g. "o+w".parse()? for world-writable objects,
+    // all of the filters must be satisfied (Unix only)
+    pub fn permission(mut self, filter: PermissionFilter) -> Self {
+        self.permission_filters.push(filter);
+        self
+    }
+
+    // Amount of threads to search with, 0 means the amount of CPUs (default)
+    // Results of multi-threaded search come in no particular order
+    // If the system can't start that many threads, the search uses the ones which started
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    // Whether results should be sorted by path
+    // Sorted search finds everything before returning the first result
+    pub fn sorted(mut self, sorted: bool) -> Self {
+        self.sorted = sorted;
+        self
+    }
+
+    // This function returns the amount of threads to search with
+    pub(crate) fn thread_count(&self) -> usize {
+        match self.threads {
+            0 => std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+            threads => threads,
+        }
+    }
+
+    // Whether any filter which can be used without name and extensions is given
+    fn has_filters(&self) -> bool {
+        self.invalid_utf8
+            || !self.file_types.is_empty()
+            || !self.size_filters.is_empty()
+            || !self.time_filters.is_empty()
+            || self.empty.is_some()
+            || self.query.is_some()
+            || self.content.is_some()
+            || !self.kinds.is_empty()
+            || self.extension_mismatch
+            || !self.owner_filters.is_empty()
+            || !self.permission_filters.is_empty()
+    }
+
+    // Validates options and creates searcher
+    pub fn build(self) -> Result<Searcher, Error> {
+        if self.root.as_os_str().is_empty() {
+            return Err(Error::EmptyPath);
+        }
+        if self.name.is_empty() && self.extensions.is_empty() && !self.has_filters() {
+            return Err(Error::NothingToSearch);
+        }
+        if let Some(max_depth) = self.max_depth.filter(|&max_depth| max_depth < self.min_depth) {
+            return Err(Error::InvalidDepth { min_depth: self.min_depth, max_depth });
+        }
+
+        // In smart case mode case sensitivity is decided for every searched text separately:
+        // the name with extensions, the query and the content, so "-g TODO" doesn't make
+        // the name search case sensitive
+        let name = std::iter::once(&self.name).chain(&self.extensions).map(String::as_str);
+        let ignore_case = self.case_mode.ignore_case(name);
+        let query_ignore_case = self.case_mode.ignore_case(self.query.as_deref());
+        let content_ignore_case = self.case_mode.ignore_case(self.content.as_deref());
+
+        let name_matcher = NameMatcher::new(&self, ignore_case)?;
+        let excludes = Excludes::new(&self)?;
+        let accounts = Accounts::load(&self.owner_filters);
+        let query = self
+            .query
+            .as_deref()
+            .map(|query| Query::parse(query, &self.root, query_ignore_case))
+            .transpose()?;
+        let content = self
+            .content
+            .as_deref()
+            .map(|content| ContentMatcher::new(&self, content, content_ignore_case))
+            .transpose()?;
+        // Extensions can be given with leading dot, e.g. ".rs"
+        let extensions = self
+            .extensions
+            .iter()
+            .map(|ext| fold_case(ext.trim_start_matches('.'), ignore_case))
+            .collect();
+
+        Ok(Searcher {
+            options: self,
+            name_matcher,
+            extensions,
+            ignore_case,
+            excludes,
+            accounts,
+            query,
+            content,
+        })
+    }
+}
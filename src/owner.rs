@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::Metadata;
+
+// Filter by owner of filesystem object, works only on Unix
+// User and group names are resolved with /etc/passwd and /etc/group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OwnerFilter {
+    // Owned by user with given uid
+    User(u32),
+    // Owned by group with given gid
+    Group(u32),
+    // Owner uid is not in /etc/passwd, e.g. the user was deleted
+    NoUser,
+    // Owner gid is not in /etc/group
+    NoGroup,
+}
+
+impl OwnerFilter {
+    // This function creates filter by user name or uid
+    pub fn user(user: &str) -> Result<Self, String> {
+        find_id("/etc/passwd", user)
+            .map(OwnerFilter::User)
+            .ok_or_else(|| format!("unknown user: {}", user))
+    }
+
+    // This function creates filter by group name or gid
+    pub fn group(group: &str) -> Result<Self, String> {
+        find_id("/etc/group", group)
+            .map(OwnerFilter::Group)
+            .ok_or_else(|| format!("unknown group: {}", group))
+    }
+
+    // Whether object owner satisfies the filter
+    #[cfg(unix)]
+    pub(crate) fn is_match(&self, metadata: &Metadata, accounts: &Accounts) -> bool {
+        use std::os::unix::fs::MetadataExt;
+
+        match self {
+            OwnerFilter::User(uid) => metadata.uid() == *uid,
+            OwnerFilter::Group(gid) => metadata.gid() == *gid,
+            OwnerFilter::NoUser => !accounts.users.contains(&metadata.uid()),
+            OwnerFilter::NoGroup => !accounts.groups.contains(&metadata.gid()),
+        }
+    }
+
+    // Other platforms have no uid and gid, so nothing matches
+    #[cfg(not(unix))]
+    pub(crate) fn is_match(&self, _metadata: &Metadata, _accounts: &Accounts) -> bool {
+        false
+    }
+}
+
+impl fmt::Display for OwnerFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OwnerFilter::User(uid) => write!(f, "uid {}", uid),
+            OwnerFilter::Group(gid) => write!(f, "gid {}", gid),
+            OwnerFilter::NoUser => write!(f, "no user"),
+            OwnerFilter::NoGroup => write!(f, "no group"),
+        }
+    }
+}
+
+// Known uids and gids, used to find objects without owner
+// Account files are read only if NoUser or NoGroup filter is given
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Accounts {
+    users: HashSet<u32>,
+    groups: HashSet<u32>,
+}
+
+impl Accounts {
+    pub(crate) fn load(filters: &[OwnerFilter]) -> Self {
+        let ids = |file, needed| match needed {
+            true => read_accounts(file).into_iter().map(|(_, id)| id).collect(),
+            false => HashSet::new(),
+        };
+
+        Accounts {
+            users: ids("/etc/passwd", filters.contains(&OwnerFilter::NoUser)),
+            groups: ids("/etc/group", filters.contains(&OwnerFilter::NoGroup)),
+        }
+    }
+}
+
+// This function finds id by name in account file, numeric ids are accepted as they are
+fn find_id(file: &str, name: &str) -> Option<u32> {
+    if let Ok(id) = name.parse() {
+        return Some(id);
+    }
+
+    read_accounts(file)
+        .into_iter()
+        .find_map(|(account, id)| (account == name).then_some(id))
+}
+
+// This function reads names and ids from /etc/passwd or /etc/group
+// Lines of both files start with "name:password:id:"
+fn read_accounts(file: &str) -> Vec<(String, u32)> {
+    let content = std::fs::read_to_string(file).unwrap_or_default();
+
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let id = fields.nth(1)?.parse().ok()?;
+            Some((name.to_owned(), id))
+        })
+        .collect()
+}
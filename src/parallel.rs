@@ -0,0 +1,161 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::empty::EmptyDirs;
+use crate::error::WalkError;
+use crate::searcher::{Match, Searcher};
+use crate::walk::{self, Dir};
+
+// Amount of results workers may produce ahead of the consumer
+const RESULTS_BUFFER: usize = 1024;
+
+// How long an idle worker sleeps before looking for work again
+const IDLE_SLEEP: Duration = Duration::from_micros(200);
+
+// State shared by all workers
+struct Shared {
+    searcher: Searcher,
+    // Work queue of every worker, workers take dirs from the back of their own queue
+    // and steal from the front of others' queues when their own one is empty
+    queues: Vec<Mutex<VecDeque<Dir>>>,
+    // Amount of dirs which are queued or being read now, search is done when it reaches 0
+    unfinished: AtomicUsize,
+    // Set when results are not needed anymore
+    quit: AtomicBool,
+    // Results of recursive emptiness checks, a dir may be checked by one worker
+    // and reached by another one
+    empty_dirs: EmptyDirs,
+}
+
+// Multi-threaded walker with work stealing
+// Workers send results through a bounded channel, so dropping the iterator
+// stops the search soon instead of walking the whole tree
+pub(crate) struct ParallelWalk {
+    results: Receiver<Result<Match, WalkError>>,
+    shared: Arc<Shared>,
+}
+
+impl ParallelWalk {
+    // This function starts the workers, None if not a single thread could be started
+    pub(crate) fn new(searcher: &Searcher, threads: usize) -> Option<Self> {
+        let root = Dir::root(searcher);
+        let shared = Arc::new(Shared {
+            searcher: searcher.clone(),
+            queues: (0..threads).map(|_| Mutex::new(VecDeque::new())).collect(),
+            unfinished: AtomicUsize::new(1),
+            quit: AtomicBool::new(false),
+            empty_dirs: EmptyDirs::default(),
+        });
+        shared.queues[0].lock().unwrap().push_back(root);
+
+        // Thread amount is user input, so the system may refuse to start all of them,
+        // the search goes on with the workers which started, other queues stay empty
+        let (sender, results) = mpsc::sync_channel(RESULTS_BUFFER);
+        let mut started = 0;
+        for worker in 0..threads {
+            let shared = Arc::clone(&shared);
+            let sender = sender.clone();
+            match thread::Builder::new().spawn(move || run_worker(&shared, worker, &sender)) {
+                Ok(_) => started += 1,
+                Err(_) => break,
+            }
+        }
+        if started == 0 {
+            return None;
+        }
+
+        Some(ParallelWalk { results, shared })
+    }
+}
+
+impl Iterator for ParallelWalk {
+    type Item = Result<Match, WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Channel is closed once all workers are finished
+        self.results.recv().ok()
+    }
+}
+
+impl Drop for ParallelWalk {
+    fn drop(&mut self) {
+        self.shared.quit.store(true, Ordering::Relaxed);
+    }
+}
+
+// Worker thread loop, reads dirs until there is no work left
+fn run_worker(shared: &Shared, worker: usize, sender: &SyncSender<Result<Match, WalkError>>) {
+    while !shared.quit.load(Ordering::Relaxed) {
+        let dir = match take_dir(shared, worker) {
+            Some(dir) => dir,
+            None if shared.unfinished.load(Ordering::Acquire) == 0 => return,
+            None => {
+                // Other workers are still reading dirs and may find new ones
+                thread::sleep(IDLE_SLEEP);
+                continue;
+            }
+        };
+
+        if !read_dir(shared, worker, &dir, sender) {
+            // Receiver was dropped, nobody needs results anymore
+            shared.quit.store(true, Ordering::Relaxed);
+        }
+        shared.unfinished.fetch_sub(1, Ordering::Release);
+    }
+}
+
+// This function takes a dir from worker's own queue or steals it from other workers
+fn take_dir(shared: &Shared, worker: usize) -> Option<Dir> {
+    if let Some(dir) = shared.queues[worker].lock().unwrap().pop_back() {
+        return Some(dir);
+    }
+
+    let threads = shared.queues.len();
+    (1..threads)
+        .map(|offset| (worker + offset) % threads)
+        .find_map(|victim| shared.queues[victim].lock().unwrap().pop_front())
+}
+
+// This function reads one dir, queueing found subdirs and sending found objects
+// Function returns false if results receiver was dropped
+fn read_dir(
+    shared: &Shared,
+    worker: usize,
+    dir: &Dir,
+    sender: &SyncSender<Result<Match, WalkError>>,
+) -> bool {
+    let files = match walk::open_dir(dir) {
+        Ok(files) => files,
+        Err(err) => return sender.send(Err(err)).is_ok(),
+    };
+
+    for entry in files {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                if sender.send(Err(WalkError::new(dir.path.clone(), err))).is_err() {
+                    return false;
+                }
+                continue;
+            }
+        };
+
+        let (subdir, found) = walk::visit_entry(&shared.searcher, &shared.empty_dirs, dir, entry);
+
+        if let Some(subdir) = subdir {
+            shared.unfinished.fetch_add(1, Ordering::AcqRel);
+            shared.queues[worker].lock().unwrap().push_back(subdir);
+        }
+        if let Some(found) = found {
+            if sender.send(Ok(found)).is_err() {
+                return false;
+            }
+        }
+    }
+
+    true
+}
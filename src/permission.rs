@@ -0,0 +1,200 @@
+use std::fmt;
+use std::fs::Metadata;
+use std::str::FromStr;
+
+// Filter by permission bits, works only on Unix, parsed from text:
+//     0o002 or 002    all given bits are set (world-writable)
+//     /0o6000         any of given bits is set (setuid or setgid)
+//     =0o644          permissions are exactly the given ones
+// Modes can also be symbolic: "u+s" (setuid), "o+w" (world-writable), "/g+w,o+w",
+// "=u+rw,g+r,o+r" with classes u, g, o, a and permissions r, w, x, s, t
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PermissionFilter {
+    // Permission bits, e.g. 0o644
+    pub mode: u32,
+    pub mode_match: ModeMatch,
+}
+
+// How permissions of object are compared with the mode of the filter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ModeMatch {
+    // All bits of the mode are set (default)
+    #[default]
+    All,
+    // Any bit of the mode is set
+    Any,
+    // Permissions are equal to the mode
+    Exact,
+}
+
+impl PermissionFilter {
+    // Whether permissions of object satisfy the filter
+    #[cfg(unix)]
+    pub(crate) fn is_match(&self, metadata: &Metadata) -> bool {
+        use std::os::unix::fs::PermissionsExt;
+
+        let permissions = metadata.permissions().mode() & 0o7777;
+        match self.mode_match {
+            ModeMatch::All => permissions & self.mode == self.mode,
+            ModeMatch::Any => permissions & self.mode != 0,
+            ModeMatch::Exact => permissions == self.mode,
+        }
+    }
+
+    // Other platforms have no permission bits, so nothing matches
+    #[cfg(not(unix))]
+    pub(crate) fn is_match(&self, _metadata: &Metadata) -> bool {
+        false
+    }
+}
+
+impl FromStr for PermissionFilter {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let (mode_match, mode) = match text.chars().next() {
+            Some('/') => (ModeMatch::Any, &text[1..]),
+            Some('=') => (ModeMatch::Exact, &text[1..]),
+            // "-" is accepted for compatibility with find
+            Some('-') => (ModeMatch::All, &text[1..]),
+            _ => (ModeMatch::All, text),
+        };
+
+        let mode = parse_mode(mode).ok_or_else(|| format!("invalid permission mode: {}", text))?;
+        Ok(PermissionFilter { mode, mode_match })
+    }
+}
+
+impl fmt::Display for PermissionFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mode_match {
+            ModeMatch::All => write!(f, "{:#o}", self.mode),
+            ModeMatch::Any => write!(f, "/{:#o}", self.mode),
+            ModeMatch::Exact => write!(f, "={:#o}", self.mode),
+        }
+    }
+}
+
+// This function parses octal ("0o755", "755") or symbolic ("u+rwx,go+rx") mode
+fn parse_mode(mode: &str) -> Option<u32> {
+    let octal = mode.strip_prefix("0o").unwrap_or(mode);
+    if !octal.is_empty() && octal.bytes().all(|c| c.is_ascii_digit()) {
+        return u32::from_str_radix(octal, 8).ok().filter(|&mode| mode <= 0o7777);
+    }
+
+    mode.split(',').try_fold(0, |mode, clause| Some(mode | parse_clause(clause)?))
+}
+
+// This function parses one clause of symbolic mode, e.g. "go+rx"
+fn parse_clause(clause: &str) -> Option<u32> {
+    let (classes, permissions) = clause.split_once('+')?;
+    if permissions.is_empty() {
+        return None;
+    }
+
+    // No classes means all of them, like in chmod
+    let classes = match classes {
+        "" => "a",
+        classes => classes,
+    };
+    let mut who = 0;
+    for class in classes.chars() {
+        who |= match class {
+            'u' => 0o4700,
+            'g' => 0o2070,
+            'o' => 0o0007,
+            'a' => 0o6777,
+            _ => return None,
+        };
+    }
+
+    let mut bits = 0;
+    for permission in permissions.chars() {
+        bits |= match permission {
+            'r' => 0o444,
+            'w' => 0o222,
+            'x' => 0o111,
+            's' => 0o6000,
+            // Sticky bit doesn't belong to any class
+            't' => {
+                who |= 0o1000;
+                0o1000
+            }
+            _ => return None,
+        };
+    }
+
+    Some(who & bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ModeMatch, PermissionFilter};
+
+    fn parse(text: &str) -> Result<(u32, ModeMatch), String> {
+        text.parse::<PermissionFilter>().map(|filter| (filter.mode, filter.mode_match))
+    }
+
+    #[test]
+    fn octal_modes() {
+        assert_eq!(parse("002"), Ok((0o002, ModeMatch::All)));
+        assert_eq!(parse("0o755"), Ok((0o755, ModeMatch::All)));
+        assert_eq!(parse("-644"), Ok((0o644, ModeMatch::All)));
+        assert_eq!(parse("/6000"), Ok((0o6000, ModeMatch::Any)));
+        assert_eq!(parse("=0o644"), Ok((0o644, ModeMatch::Exact)));
+        assert!(parse("8").is_err());
+        assert!(parse("17777").is_err());
+    }
+
+    #[test]
+    fn symbolic_modes() {
+        assert_eq!(parse("u+s"), Ok((0o4000, ModeMatch::All)));
+        assert_eq!(parse("g+s"), Ok((0o2000, ModeMatch::All)));
+        assert_eq!(parse("o+w"), Ok((0o002, ModeMatch::All)));
+        assert_eq!(parse("/g+w,o+w"), Ok((0o022, ModeMatch::Any)));
+        assert_eq!(parse("=u+rw,g+r,o+r"), Ok((0o644, ModeMatch::Exact)));
+        assert_eq!(parse("go+rx"), Ok((0o055, ModeMatch::All)));
+        assert_eq!(parse("+x"), Ok((0o111, ModeMatch::All)));
+        assert_eq!(parse("a+s"), Ok((0o6000, ModeMatch::All)));
+        assert_eq!(parse("+t"), Ok((0o1000, ModeMatch::All)));
+        assert_eq!(parse("o+t"), Ok((0o1000, ModeMatch::All)));
+    }
+
+    #[test]
+    fn invalid_modes() {
+        for text in ["", "/", "u", "u+", "u-w", "z+r", "u+q", "u+r,", "0o"] {
+            assert!(parse(text).is_err(), "{} should be invalid", text);
+        }
+    }
+
+    #[test]
+    fn display() {
+        for text in ["0o2", "/0o6000", "=0o644"] {
+            assert_eq!(text.parse::<PermissionFilter>().unwrap().to_string(), text);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn matching() {
+        use std::fs::{self, Permissions};
+        use std::os::unix::fs::PermissionsExt;
+
+        use crate::temp_dir::TempDir;
+
+        let dir = TempDir::new("permission");
+        let path = dir.join("file");
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, Permissions::from_mode(0o640)).unwrap();
+        let metadata = fs::metadata(&path).unwrap();
+
+        let is_match = |text: &str| text.parse::<PermissionFilter>().unwrap().is_match(&metadata);
+        assert!(is_match("u+rw"));
+        assert!(!is_match("u+rwx"));
+        assert!(is_match("/u+x,g+r"));
+        assert!(!is_match("/o+rwx"));
+        assert!(is_match("=640"));
+        assert!(!is_match("=600"));
+    }
+}
@@ -0,0 +1,582 @@
+// Query expression language, e.g.
+//     name:report* and (ext:pdf or ext:docx) and not path:archive and size>1M
+//
+// Grammar:
+//     expression := and ("or" and)*
+//     and        := not ("and" not)*
+//     not        := "not" not | "(" expression ")" | predicate
+//     predicate  := field ":" value | field operator value | value
+//
+// Fields:
+//     name:TEXT            file name (with extension), a value without field means the same
+//     path:TEXT            path relative to the search dir, with "/" separators
+//     ext:EXT              file extension, e.g. "pdf" or "tar.gz"
+//     type:TYPE            file type, see FileType (f, d, l, s, p, b, c, x)
+//     size OP SIZE         file size, e.g. "size>1M" or "size<=10KiB"
+//     depth OP N           amount of dirs between the search dir and the object
+//     mtime OP TIME        modification time (also atime, ctime and btime), "mtime>2d" means
+//                          "modified during last two days", see parse_time() for formats
+// Operators are <, <=, =, >=, > (":" means "="), texts with glob characters (*?[{) are globs,
+// other texts are substrings. Values with spaces or parentheses can be put into double quotes
+// Keywords and, or, not are lowercase, "and" takes precedence over "or"
+
+use std::cell::OnceCell;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::error::Error;
+use crate::file_type::FileType;
+use crate::glob::Glob;
+use crate::ignore::slash_path;
+use crate::matcher::{fold_case, has_extension};
+use crate::size::parse_size;
+use crate::time::{parse_time, TimeField};
+
+// Parsed query, evaluated for every object found by the walk
+#[derive(Debug, Clone)]
+pub(crate) struct Query {
+    expression: Expression,
+    // Relative paths are computed by stripping the search dir from found paths
+    root: PathBuf,
+    ignore_case: bool,
+}
+
+impl Query {
+    // This function parses query, errors point at the column (counted from 1) of the problem
+    pub(crate) fn parse(query: &str, root: &Path, ignore_case: bool) -> Result<Self, Error> {
+        let invalid_query = |(column, message)| Error::InvalidQuery {
+            query: query.to_owned(),
+            column,
+            message,
+        };
+
+        let tokens = tokenize(query).map_err(invalid_query)?;
+        let mut parser = Parser { tokens, position: 0, ignore_case };
+        let expression = parser.parse().map_err(invalid_query)?;
+
+        Ok(Query { expression, root: root.to_owned(), ignore_case })
+    }
+
+    // Whether given object satisfies the query
+    pub(crate) fn is_match(&self, path: &Path, is_dir: bool, metadata: Option<&Metadata>, depth: usize) -> bool {
+        let entry = Entry {
+            path,
+            is_dir,
+            metadata,
+            depth,
+            query: self,
+            name: OnceCell::new(),
+            relative: OnceCell::new(),
+            link_metadata: OnceCell::new(),
+        };
+        self.expression.is_match(&entry)
+    }
+}
+
+// Node of query syntax tree
+#[derive(Debug, Clone)]
+enum Expression {
+    And(Vec<Expression>),
+    Or(Vec<Expression>),
+    Not(Box<Expression>),
+    Predicate(Predicate),
+}
+
+impl Expression {
+    fn is_match(&self, entry: &Entry) -> bool {
+        match self {
+            Expression::And(expressions) => expressions.iter().all(|expression| expression.is_match(entry)),
+            Expression::Or(expressions) => expressions.iter().any(|expression| expression.is_match(entry)),
+            Expression::Not(expression) => !expression.is_match(entry),
+            Expression::Predicate(predicate) => predicate.is_match(entry),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Predicate {
+    Name(Text),
+    Path(Text),
+    // Extension without leading dot, in lowercase if case is ignored
+    Extension(String),
+    Type(FileType),
+    Size(Comparison, u64),
+    Depth(Comparison, usize),
+    Time(TimeField, Comparison, SystemTime),
+}
+
+impl Predicate {
+    fn is_match(&self, entry: &Entry) -> bool {
+        match self {
+            Predicate::Name(text) => text.is_match(entry.name()),
+            Predicate::Path(text) => text.is_match(entry.relative()),
+            // Dirs have no extensions, like in the name search
+            Predicate::Extension(extension) => !entry.is_dir && has_extension(entry.name().as_bytes(), extension),
+            Predicate::Type(file_type) => file_type.is_match(entry.path, entry.metadata, entry.link_metadata()),
+            // Size applies only to files, like size filters
+            Predicate::Size(comparison, size) => {
+                !entry.is_dir && entry.metadata.is_some_and(|metadata| comparison.holds(metadata.len(), *size))
+            }
+            Predicate::Depth(comparison, depth) => comparison.holds(entry.depth, *depth),
+            Predicate::Time(field, comparison, time) => entry
+                .metadata
+                .and_then(|metadata| field.get(metadata))
+                .is_some_and(|value| comparison.holds(value, *time)),
+        }
+    }
+}
+
+// Text value of name and path predicates, in lowercase if case is ignored
+#[derive(Debug, Clone)]
+enum Text {
+    Substring(String),
+    Glob(Glob),
+}
+
+impl Text {
+    // Texts with glob characters are globs, others are substrings
+    fn new(text: &str, column: usize, ignore_case: bool) -> Result<Self, ParseError> {
+        let text = fold_case(text, ignore_case);
+
+        if text.contains(['*', '?', '[', '{']) {
+            Glob::new(&text)
+                .map(Text::Glob)
+                .map_err(|message| (column, format!("invalid glob: {}", message)))
+        } else {
+            Ok(Text::Substring(text))
+        }
+    }
+
+    fn is_match(&self, text: &str) -> bool {
+        match self {
+            Text::Substring(substring) => text.contains(substring.as_str()),
+            Text::Glob(glob) => glob.is_match(text),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    // Whether value is in the relation with the bound, e.g. value > bound
+    fn holds<T: PartialOrd>(self, value: T, bound: T) -> bool {
+        match self {
+            Comparison::Less => value < bound,
+            Comparison::LessOrEqual => value <= bound,
+            Comparison::Equal => value == bound,
+            Comparison::GreaterOrEqual => value >= bound,
+            Comparison::Greater => value > bound,
+        }
+    }
+}
+
+// Object the query is evaluated for
+// Name, relative path and metadata of symlinks are computed only if a predicate needs them
+struct Entry<'a> {
+    path: &'a Path,
+    is_dir: bool,
+    // Metadata which follows symlinks
+    metadata: Option<&'a Metadata>,
+    depth: usize,
+    query: &'a Query,
+    name: OnceCell<String>,
+    relative: OnceCell<String>,
+    link_metadata: OnceCell<Option<Metadata>>,
+}
+
+impl Entry<'_> {
+    fn name(&self) -> &str {
+        self.name.get_or_init(|| {
+            let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+            fold_case(&name, self.query.ignore_case)
+        })
+    }
+
+    fn relative(&self) -> &str {
+        self.relative.get_or_init(|| {
+            let relative = self.path.strip_prefix(&self.query.root).unwrap_or(self.path);
+            fold_case(&slash_path(relative), self.query.ignore_case)
+        })
+    }
+
+    fn link_metadata(&self) -> Option<&Metadata> {
+        self.link_metadata
+            .get_or_init(|| std::fs::symlink_metadata(self.path).ok())
+            .as_ref()
+    }
+}
+
+// Error of parsing: column of the problem (counted from 1) and description
+type ParseError = (usize, String);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Word(String),
+    End,
+}
+
+#[derive(Debug)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+// This function splits query into parentheses, keywords and words
+// Parts of words can be put into double quotes, quoted words are never keywords
+fn tokenize(query: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().zip(1..).peekable();
+
+    while let Some(&(c, column)) = chars.peek() {
+        let kind = match c {
+            _ if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' => {
+                chars.next();
+                TokenKind::Open
+            }
+            ')' => {
+                chars.next();
+                TokenKind::Close
+            }
+            _ => {
+                let mut word = String::new();
+                let mut quoted = false;
+
+                while let Some(&(c, quote_column)) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    chars.next();
+                    if c != '"' {
+                        word.push(c);
+                        continue;
+                    }
+
+                    quoted = true;
+                    loop {
+                        match chars.next() {
+                            Some(('"', _)) => break,
+                            Some((c, _)) => word.push(c),
+                            None => return Err((quote_column, "unclosed quote".to_owned())),
+                        }
+                    }
+                }
+
+                match word.as_str() {
+                    "and" if !quoted => TokenKind::And,
+                    "or" if !quoted => TokenKind::Or,
+                    "not" if !quoted => TokenKind::Not,
+                    _ => TokenKind::Word(word),
+                }
+            }
+        };
+        tokens.push(Token { kind, column });
+    }
+
+    let end = query.chars().count() + 1;
+    tokens.push(Token { kind: TokenKind::End, column: end });
+    Ok(tokens)
+}
+
+// Recursive descent parser over tokens, the last token is always End
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    ignore_case: bool,
+}
+
+impl Parser {
+    fn parse(&mut self) -> Result<Expression, ParseError> {
+        if self.peek().kind == TokenKind::End {
+            return Err((1, "query is empty".to_owned()));
+        }
+
+        let expression = self.parse_or()?;
+        let token = self.peek();
+        match token.kind {
+            TokenKind::End => Ok(expression),
+            TokenKind::Close => Err((token.column, "unmatched ')'".to_owned())),
+            _ => Err((token.column, "expected 'and' or 'or'".to_owned())),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expression, ParseError> {
+        let mut expressions = vec![self.parse_and()?];
+        while self.peek().kind == TokenKind::Or {
+            self.position += 1;
+            expressions.push(self.parse_and()?);
+        }
+
+        Ok(match expressions.len() {
+            1 => expressions.remove(0),
+            _ => Expression::Or(expressions),
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Expression, ParseError> {
+        let mut expressions = vec![self.parse_not()?];
+        while self.peek().kind == TokenKind::And {
+            self.position += 1;
+            expressions.push(self.parse_not()?);
+        }
+
+        Ok(match expressions.len() {
+            1 => expressions.remove(0),
+            _ => Expression::And(expressions),
+        })
+    }
+
+    fn parse_not(&mut self) -> Result<Expression, ParseError> {
+        let token = &self.tokens[self.position];
+        let (kind, column) = (token.kind.clone(), token.column);
+        self.position += 1;
+
+        match kind {
+            TokenKind::Not => Ok(Expression::Not(Box::new(self.parse_not()?))),
+            TokenKind::Open => {
+                let expression = self.parse_or()?;
+                let token = self.peek();
+                match token.kind {
+                    TokenKind::Close => {
+                        self.position += 1;
+                        Ok(expression)
+                    }
+                    TokenKind::End => Err((column, "unclosed '('".to_owned())),
+                    _ => Err((token.column, "expected 'and', 'or' or ')'".to_owned())),
+                }
+            }
+            TokenKind::Word(word) => parse_predicate(&word, column, self.ignore_case).map(Expression::Predicate),
+            TokenKind::Close => Err((column, "expected predicate, found ')'".to_owned())),
+            TokenKind::And => Err((column, "expected predicate, found 'and'".to_owned())),
+            TokenKind::Or => Err((column, "expected predicate, found 'or'".to_owned())),
+            TokenKind::End => {
+                // End token is never skipped
+                self.position -= 1;
+                Err((column, "unexpected end of query".to_owned()))
+            }
+        }
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position]
+    }
+}
+
+// This function parses predicate like "name:report*" or "size>1M"
+// Column is the column of the word
+fn parse_predicate(word: &str, column: usize, ignore_case: bool) -> Result<Predicate, ParseError> {
+    let field_end = word.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(word.len());
+    let (field, rest) = word.split_at(field_end);
+
+    let operators = [
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+        ("=", Comparison::Equal),
+    ];
+    let operator = operators.into_iter().find(|(operator, _)| rest.starts_with(operator));
+
+    let (operator, comparison, value) = match operator {
+        Some((operator, comparison)) => (operator, Some(comparison), &rest[operator.len()..]),
+        None => match rest.strip_prefix(':') {
+            Some(value) => (":", None, value),
+            // Word without field is a name
+            None => return Text::new(word, column, ignore_case).map(Predicate::Name),
+        },
+    };
+
+    let operator_column = column + field.chars().count();
+    let value_column = operator_column + operator.len();
+    if value.is_empty() {
+        return Err((value_column, format!("missing value for '{}'", field)));
+    }
+    let text_only = |predicate| match comparison {
+        None => Ok(predicate),
+        Some(_) => Err((operator_column, format!("'{}' can be used only with ':'", field))),
+    };
+    let invalid_value = |message| (value_column, message);
+
+    match field {
+        "name" => text_only(Predicate::Name(Text::new(value, value_column, ignore_case)?)),
+        "path" => text_only(Predicate::Path(Text::new(value, value_column, ignore_case)?)),
+        "ext" | "extension" => {
+            let extension = fold_case(value.trim_start_matches('.'), ignore_case);
+            text_only(Predicate::Extension(extension))
+        }
+        "type" => text_only(Predicate::Type(value.parse().map_err(invalid_value)?)),
+        "size" => {
+            let size = parse_size(value).map_err(invalid_value)?;
+            Ok(Predicate::Size(comparison.unwrap_or(Comparison::Equal), size))
+        }
+        "depth" => {
+            let depth = value.parse().map_err(|_| invalid_value(format!("invalid depth: {}", value)))?;
+            Ok(Predicate::Depth(comparison.unwrap_or(Comparison::Equal), depth))
+        }
+        _ => {
+            let field = field
+                .parse::<TimeField>()
+                .map_err(|_| (column, format!("unknown field '{}'", field)))?;
+            let comparison = match comparison {
+                Some(comparison @ (Comparison::Less | Comparison::LessOrEqual)) => comparison,
+                Some(comparison @ (Comparison::Greater | Comparison::GreaterOrEqual)) => comparison,
+                _ => return Err((operator_column, "times can be compared only with '<' or '>'".to_owned())),
+            };
+            let time = parse_time(value).map_err(invalid_value)?;
+            Ok(Predicate::Time(field, comparison, time))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    use super::Query;
+    use crate::error::Error;
+    use crate::temp_dir::TempDir;
+
+    fn root() -> PathBuf {
+        PathBuf::from("/search")
+    }
+
+    // This function evaluates query for object without metadata
+    fn is_match(query: &str, path: &str) -> bool {
+        let query = Query::parse(query, &root(), true).unwrap();
+        let path = root().join(path);
+        let depth = path.strip_prefix(root()).unwrap().components().count();
+        query.is_match(&path, false, None, depth)
+    }
+
+    // This function returns column and message of query parse error
+    fn error(query: &str) -> (usize, String) {
+        match Query::parse(query, &root(), true) {
+            Err(Error::InvalidQuery { column, message, .. }) => (column, message),
+            other => panic!("expected parse error for {}, got {:?}", query, other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn precedence() {
+        // "and" takes precedence over "or"
+        assert!(is_match("name:x or name:report and ext:pdf", "x.txt"));
+        assert!(!is_match("(name:x or name:report) and ext:pdf", "x.txt"));
+        assert!(is_match("(name:x or name:report) and ext:pdf", "report.pdf"));
+    }
+
+    #[test]
+    fn not() {
+        assert!(is_match("not ext:pdf", "report.txt"));
+        assert!(!is_match("not ext:pdf", "report.pdf"));
+        assert!(is_match("not not ext:pdf", "report.pdf"));
+        assert!(is_match("name:report and not (ext:pdf or path:archive)", "report.txt"));
+        assert!(!is_match("name:report and not (ext:pdf or path:archive)", "archive/report.txt"));
+    }
+
+    #[test]
+    fn texts() {
+        // Texts without glob characters are substrings, a word without field is a name
+        assert!(is_match("port", "docs/report.pdf"));
+        assert!(is_match("name:report*", "report_2026.pdf"));
+        assert!(!is_match("name:report*", "old_report.pdf"));
+        assert!(is_match("path:docs/*.pdf", "docs/report.pdf"));
+        assert!(!is_match("path:docs/*.pdf", "docs/old/report.pdf"));
+        assert!(is_match("name:REPORT", "Report.pdf"));
+        assert!(is_match("depth>=2", "docs/report.pdf"));
+        assert!(!is_match("depth=1", "docs/report.pdf"));
+    }
+
+    #[test]
+    fn quoting() {
+        assert!(is_match("name:\"my report\"", "my report.pdf"));
+        assert!(!is_match("name:\"my report\"", "my_report.pdf"));
+        assert!(is_match("name:\"(draft)\"", "report (draft).pdf"));
+        // Quoted keywords are words
+        assert!(is_match("\"and\"", "band.txt"));
+        assert!(is_match("name:\"not\" or name:x", "notes.txt"));
+    }
+
+    #[test]
+    fn extensions() {
+        assert!(is_match("ext:tar.gz", "backup.tar.gz"));
+        assert!(is_match("ext:.gz", "backup.tar.gz"));
+        assert!(!is_match("ext:tar.gz", "backup.gz"));
+        assert!(!is_match("ext:gz", ".gz"));
+        assert!(is_match("ext:PDF", "report.pdf"));
+    }
+
+    #[test]
+    fn error_columns() {
+        assert_eq!(error(""), (1, "query is empty".to_owned()));
+        assert_eq!(error("name:a and"), (11, "unexpected end of query".to_owned()));
+        assert_eq!(error("name:a )"), (8, "unmatched ')'".to_owned()));
+        assert_eq!(error("(name:a"), (1, "unclosed '('".to_owned()));
+        assert_eq!(error("(name:a name:b"), (9, "expected 'and', 'or' or ')'".to_owned()));
+        assert_eq!(error("name:a name:b"), (8, "expected 'and' or 'or'".to_owned()));
+        assert_eq!(error("and name:a"), (1, "expected predicate, found 'and'".to_owned()));
+        assert_eq!(error("name:a or or"), (11, "expected predicate, found 'or'".to_owned()));
+        assert_eq!(error("()"), (2, "expected predicate, found ')'".to_owned()));
+        assert_eq!(error("name:\"a b"), (6, "unclosed quote".to_owned()));
+        assert_eq!(error("ext:pdf and size>"), (18, "missing value for 'size'".to_owned()));
+        assert_eq!(error("colour:red"), (1, "unknown field 'colour'".to_owned()));
+        assert_eq!(error("x and name>a"), (11, "'name' can be used only with ':'".to_owned()));
+        assert_eq!(error("mtime=2d"), (6, "times can be compared only with '<' or '>'".to_owned()));
+        assert_eq!(error("size>1X").0, 6);
+        assert_eq!(error("type:q").0, 6);
+        assert_eq!(error("depth>x").0, 7);
+        assert_eq!(error("name:[ab").0, 6);
+    }
+
+    #[test]
+    fn error_display() {
+        let error = Query::parse("name:a )", &root(), true).err().unwrap();
+        assert_eq!(error.to_string(), "invalid query at column 8: unmatched ')'\nname:a )\n       ^");
+    }
+
+    #[test]
+    fn metadata_predicates() {
+        let temp = TempDir::new("query");
+        let dir = temp.path();
+        let big = dir.join("big.bin");
+        let old = dir.join("old.txt");
+        File::create(&big).unwrap().set_len(2_000_000).unwrap();
+        let file = File::create(&old).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(10 * 24 * 60 * 60)).unwrap();
+        drop(file);
+
+        let is_match = |query: &str, path: &Path| {
+            let query = Query::parse(query, dir, true).unwrap();
+            let metadata = fs::metadata(path).unwrap();
+            query.is_match(path, metadata.is_dir(), Some(&metadata), 1)
+        };
+
+        assert!(is_match("size>1M", &big));
+        assert!(!is_match("size>1M", &old));
+        assert!(is_match("size<=2MB and size>=2000000", &big));
+        assert!(is_match("size=0", &old));
+        assert!(!is_match("size>=0", dir));
+        assert!(is_match("mtime>2d", &big));
+        assert!(!is_match("mtime>2d", &old));
+        assert!(is_match("mtime<1w", &old));
+        assert!(is_match("type:f", &big));
+        assert!(is_match("type:d", dir));
+    }
+}
@@ -0,0 +1,230 @@
+use std::borrow::Cow;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+
+use crate::content::{ContentMatcher, Line};
+use crate::empty::EmptyDirs;
+use crate::encoding::Encoding;
+use crate::error::WalkError;
+use crate::iter::Iter;
+use crate::kind::Format;
+use crate::exclude::Excludes;
+use crate::file_type::FileType;
+use crate::matcher::{has_extension, os_str_bytes, MatchMode, NameMatcher};
+use crate::options::SearchOptions;
+use crate::owner::Accounts;
+use crate::query::Query;
+
+// Filesystem object which satisfies search options
+#[derive(Debug, Clone)]
+pub struct Match {
+    // Path to found object (search path joined with path inside it)
+    pub path: PathBuf,
+    // Whether found object is a dir
+    pub is_dir: bool,
+    // Object size in bytes, None if metadata couldn't be read
+    pub size: Option<u64>,
+    // Amount of dirs between the search dir and found object, 1 for objects in the search dir
+    pub depth: usize,
+    // How well the name matches in fuzzy match mode (the higher the better), None in other modes
+    pub score: Option<i64>,
+    // Matching lines with context lines when file content is searched, empty otherwise
+    pub lines: Vec<Line>,
+    // Encoding of file content when content is searched or encodings are detected,
+    // None for dirs and in other cases
+    pub encoding: Option<Encoding>,
+    // Format of file content detected by magic numbers when kinds or extension mismatches
+    // are searched, None if it is unknown and in other cases
+    pub format: Option<Format>,
+}
+
+impl Match {
+    pub(crate) fn new(path: PathBuf, is_dir: bool, metadata: Option<&Metadata>, depth: usize, score: Option<i64>) -> Self {
+        let size = metadata.map(Metadata::len);
+        Match { path, is_dir, size, depth, score, lines: Vec::new(), encoding: None, format: None }
+    }
+}
+
+// File searcher created from SearchOptions::build()
+#[derive(Debug, Clone)]
+pub struct Searcher {
+    pub(crate) options: SearchOptions,
+    pub(crate) name_matcher: NameMatcher,
+    // Searched extensions, in lowercase if case is ignored
+    pub(crate) extensions: Vec<String>,
+    pub(crate) ignore_case: bool,
+    pub(crate) excludes: Excludes,
+    pub(crate) accounts: Accounts,
+    pub(crate) query: Option<Query>,
+    pub(crate) content: Option<ContentMatcher>,
+}
+
+impl Searcher {
+    // Options this searcher was built with
+    pub fn options(&self) -> &SearchOptions {
+        &self.options
+    }
+
+    // This function returns lazy iterator over found objects
+    // Searcher can look for: only filename (without extension), only extension (or
+    // several extensions), both filename and extension(s)
+    pub fn iter(&self) -> Iter<'_> {
+        Iter::new(self)
+    }
+
+    // This function checks whether given filesystem object satisfies search options
+    // and returns the match if it does
+    // Empty dirs cache belongs to the walk which found the object
+    pub(crate) fn find(&self, path: PathBuf, is_dir: bool, depth: usize, empty_dirs: &EmptyDirs) -> Option<Match> {
+        let extensions = &self.extensions;
+        let no_extensions = extensions.is_empty();
+        let empty_filename = self.options.name.is_empty();
+
+        if self.options.invalid_utf8 && path.file_name().is_some_and(|name| name.to_str().is_some()) {
+            return None;
+        }
+
+        let file_name = os_str_bytes(path.file_name(), self.ignore_case);
+        let extension = self.find_extension(&file_name);
+        let extension_found = extension.is_some();
+        let extension_matches = no_extensions || extension_found;
+
+        // Name without found extension, for "archive.tar.gz" and "tar.gz" extension it is "archive"
+        let stem = match extension {
+            Some(extension) => Cow::Borrowed(&file_name[..file_name.len() - extension.len() - 1]),
+            None => os_str_bytes(path.file_stem(), self.ignore_case),
+        };
+
+        let score = if empty_filename && no_extensions {
+            // Searching only by filters
+            0
+        } else if empty_filename {
+            // Searching only by extension(s), dirs can't match then
+            (!is_dir && extension_found).then_some(0)?
+        } else if is_dir {
+            // Dir matches by filename
+            if !no_extensions {
+                return None;
+            }
+            self.name_matcher.score(&path, &stem)?
+        } else if extension_matches {
+            // Files and other non-dir objects (symlinks, sockets, pipes, devices) match
+            // by filename and extension in the same way
+            self.name_matcher.score(&path, &stem)?
+        } else {
+            return None;
+        };
+
+        // Metadata is read only for objects with matching names, once for filters and the match
+        let metadata = std::fs::metadata(&path).ok();
+
+        if !self.matches_filters(&path, metadata.as_ref(), is_dir, empty_dirs) {
+            return None;
+        }
+
+        if let Some(query) = &self.query {
+            if !query.is_match(&path, is_dir, metadata.as_ref(), depth) {
+                return None;
+            }
+        }
+
+        // Content is read only when all other checks passed, only files have content
+        let is_file = metadata.as_ref().is_some_and(Metadata::is_file);
+        let options = &self.options;
+
+        let sniffed = !options.kinds.is_empty() || options.extension_mismatch;
+        let format = match sniffed && is_file {
+            true => Format::of_file(&path),
+            false => None,
+        };
+        if !options.kinds.is_empty() && !format.is_some_and(|format| options.kinds.contains(&format.kind)) {
+            return None;
+        }
+        if options.extension_mismatch && !(is_file && Format::is_mismatch(format, &path)) {
+            return None;
+        }
+        let (encoding, lines) = match &self.content {
+            Some(content) if is_file => content.search(&path).map(|(encoding, lines)| (Some(encoding), lines))?,
+            Some(_) => return None,
+            None if is_file && self.options.detect_encoding => (Encoding::of_file(&path), Vec::new()),
+            None => (None, Vec::new()),
+        };
+
+        let score = (self.options.match_mode == MatchMode::Fuzzy).then_some(score);
+        Some(Match { lines, encoding, format, ..Match::new(path, is_dir, metadata.as_ref(), depth, score) })
+    }
+
+    // This function checks filters which need object metadata
+    fn matches_filters(&self, path: &Path, metadata: Option<&Metadata>, is_dir: bool, empty_dirs: &EmptyDirs) -> bool {
+        let options = &self.options;
+
+        // Object must have one of given types, its own metadata is read only for type filters
+        if !options.file_types.is_empty() {
+            let link_metadata = std::fs::symlink_metadata(path).ok();
+            let matches_type = |file_type: &FileType| file_type.is_match(path, metadata, link_metadata.as_ref());
+            if !options.file_types.iter().any(matches_type) {
+                return false;
+            }
+        }
+
+        // Size filters apply only to files
+        if !options.size_filters.is_empty() {
+            let size = match metadata {
+                Some(metadata) if !is_dir => metadata.len(),
+                _ => return false,
+            };
+            if !options.size_filters.iter().all(|filter| filter.is_match(size)) {
+                return false;
+            }
+        }
+
+        if let Some(empty) = options.empty {
+            if !metadata.is_some_and(|metadata| empty.is_match(path, metadata, empty_dirs)) {
+                return false;
+            }
+        }
+
+        // Ownership and permissions are checked for all objects
+        let owner_filters = &options.owner_filters;
+        let permission_filters = &options.permission_filters;
+        if !owner_filters.is_empty() || !permission_filters.is_empty() {
+            let metadata = match metadata {
+                Some(metadata) => metadata,
+                None => return false,
+            };
+            if !owner_filters.iter().all(|filter| filter.is_match(metadata, &self.accounts))
+                || !permission_filters.iter().all(|filter| filter.is_match(metadata))
+            {
+                return false;
+            }
+        }
+
+        if !options.time_filters.is_empty() {
+            match metadata {
+                Some(metadata) if options.time_filters.iter().all(|filter| filter.is_match(metadata)) => {}
+                _ => return false,
+            }
+        }
+
+        true
+    }
+
+    // This function returns the longest searched extension the file name ends with
+    // Extensions may consist of several parts, e.g. "tar.gz" or "d.ts"
+    fn find_extension(&self, file_name: &[u8]) -> Option<&str> {
+        self.extensions
+            .iter()
+            .filter(|extension| has_extension(file_name, extension))
+            .max_by_key(|extension| extension.len())
+            .map(String::as_str)
+    }
+}
+
+impl<'a> IntoIterator for &'a Searcher {
+    type Item = Result<Match, WalkError>;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
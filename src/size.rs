@@ -0,0 +1,178 @@
+use std::fmt;
+use std::str::FromStr;
+
+// File size filter, parsed from text:
+//     +100M       at least 100 megabytes
+//     -4k         at most 4 kilobytes
+//     10KiB..2GiB from 10 kibibytes to 2 gibibytes (both bounds are optional, e.g. "..1G")
+//     512         exactly 512 bytes
+// Units are case insensitive, SI units are powers of 1000 (k, M, G, T, P with optional "B")
+// and IEC units are powers of 1024 (Ki, Mi, Gi, Ti, Pi with optional "B")
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeFilter {
+    // Minimal size in bytes (inclusive)
+    pub min: Option<u64>,
+    // Maximal size in bytes (inclusive)
+    pub max: Option<u64>,
+}
+
+impl SizeFilter {
+    // Whether given size in bytes satisfies the filter
+    pub fn is_match(&self, size: u64) -> bool {
+        self.min.is_none_or(|min| size >= min) && self.max.is_none_or(|max| size <= max)
+    }
+}
+
+impl FromStr for SizeFilter {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+
+        if let Some(size) = text.strip_prefix('+') {
+            return Ok(SizeFilter { min: Some(parse_size(size)?), max: None });
+        }
+        if let Some(size) = text.strip_prefix('-') {
+            return Ok(SizeFilter { min: None, max: Some(parse_size(size)?) });
+        }
+        if let Some((min, max)) = text.split_once("..") {
+            let parse_bound = |bound: &str| match bound.trim() {
+                "" => Ok(None),
+                bound => parse_size(bound).map(Some),
+            };
+            let filter = SizeFilter { min: parse_bound(min)?, max: parse_bound(max)? };
+
+            if let (Some(min), Some(max)) = (filter.min, filter.max) {
+                if min > max {
+                    return Err(format!("size range start is bigger than its end: {}", text));
+                }
+            }
+            return Ok(filter);
+        }
+
+        let size = parse_size(text)?;
+        Ok(SizeFilter { min: Some(size), max: Some(size) })
+    }
+}
+
+impl fmt::Display for SizeFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min == max => write!(f, "{}", min),
+            (Some(min), None) => write!(f, "+{}", min),
+            (None, Some(max)) => write!(f, "-{}", max),
+            (min, max) => {
+                if let Some(min) = min {
+                    write!(f, "{}", min)?;
+                }
+                write!(f, "..")?;
+                if let Some(max) = max {
+                    write!(f, "{}", max)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// This function parses size with optional unit to bytes, e.g. "1.5M" or "10KiB"
+pub(crate) fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let number_end = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(number_end);
+
+    let number: f64 = number.parse().map_err(|_| format!("invalid size: {}", text))?;
+    let multiplier = unit_multiplier(unit.trim()).ok_or_else(|| format!("unknown size unit: {}", unit))?;
+
+    let bytes = number * multiplier as f64;
+    if bytes > u64::MAX as f64 {
+        return Err(format!("size is too big: {}", text));
+    }
+
+    Ok(bytes.round() as u64)
+}
+
+// This function returns the amount of bytes in given unit
+fn unit_multiplier(unit: &str) -> Option<u64> {
+    let unit = unit.to_lowercase();
+    // "B" in the end is optional: "k" and "kB", "Ki" and "KiB" mean the same
+    let unit = match unit.strip_suffix('b') {
+        Some(prefix) if !prefix.is_empty() => prefix,
+        _ => unit.as_str(),
+    };
+
+    let (prefix, base) = match unit.strip_suffix('i') {
+        Some(prefix) => (prefix, 1024u64),
+        None => (unit, 1000u64),
+    };
+
+    let power = match prefix {
+        "" | "b" if base == 1000 => 0,
+        "k" => 1,
+        "m" => 2,
+        "g" => 3,
+        "t" => 4,
+        "p" => 5,
+        _ => return None,
+    };
+
+    Some(base.pow(power))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_size, SizeFilter};
+
+    #[test]
+    fn units() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("512b"), Ok(512));
+        assert_eq!(parse_size("1k"), Ok(1000));
+        assert_eq!(parse_size("1kB"), Ok(1000));
+        assert_eq!(parse_size("1KiB"), Ok(1024));
+        assert_eq!(parse_size("1ki"), Ok(1024));
+        assert_eq!(parse_size("1.5M"), Ok(1_500_000));
+        assert_eq!(parse_size("2GiB"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("1P"), Ok(1_000_000_000_000_000));
+    }
+
+    #[test]
+    fn invalid_sizes() {
+        assert!(parse_size("").is_err());
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("1.2.3").is_err());
+        assert!(parse_size("1X").is_err());
+        assert!(parse_size("1bi").is_err());
+        assert!(parse_size("100000P").is_err());
+    }
+
+    #[test]
+    fn filters() {
+        let at_least: SizeFilter = "+100M".parse().unwrap();
+        assert_eq!(at_least, SizeFilter { min: Some(100_000_000), max: None });
+        assert!(at_least.is_match(100_000_000));
+        assert!(!at_least.is_match(99_999_999));
+
+        let at_most: SizeFilter = "-4k".parse().unwrap();
+        assert_eq!(at_most, SizeFilter { min: None, max: Some(4000) });
+
+        let exact: SizeFilter = "512".parse().unwrap();
+        assert!(exact.is_match(512));
+        assert!(!exact.is_match(513));
+
+        let range: SizeFilter = "10KiB..2GiB".parse().unwrap();
+        assert_eq!(range, SizeFilter { min: Some(10 * 1024), max: Some(2 * 1024 * 1024 * 1024) });
+        assert_eq!("..1G".parse(), Ok(SizeFilter { min: None, max: Some(1_000_000_000) }));
+        assert!("2G..1G".parse::<SizeFilter>().is_err());
+    }
+
+    #[test]
+    fn display() {
+        let cases = [("+100", "+100"), ("-4k", "-4000"), ("512", "512"), ("10..20", "10..20"), ("..20", "-20")];
+        for (text, displayed) in cases {
+            assert_eq!(text.parse::<SizeFilter>().unwrap().to_string(), displayed);
+        }
+    }
+}
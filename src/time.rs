@@ -0,0 +1,287 @@
+use std::fmt;
+use std::fs::Metadata;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+
+// Which time of filesystem object is compared
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeField {
+    // Last modification time (mtime, default)
+    #[default]
+    Modified,
+    // Last access time (atime)
+    Accessed,
+    // Last status change time (ctime), available only on Unix
+    Changed,
+    // Creation time (btime), not supported by some filesystems
+    Created,
+}
+
+impl TimeField {
+    // This function reads the time from metadata, None if it is not available
+    pub fn get(&self, metadata: &Metadata) -> Option<SystemTime> {
+        match self {
+            TimeField::Modified => metadata.modified().ok(),
+            TimeField::Accessed => metadata.accessed().ok(),
+            TimeField::Created => metadata.created().ok(),
+            TimeField::Changed => change_time(metadata),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn change_time(metadata: &Metadata) -> Option<SystemTime> {
+    use std::os::unix::fs::MetadataExt;
+
+    let seconds = Duration::from_secs(metadata.ctime().unsigned_abs());
+    let time = match metadata.ctime() >= 0 {
+        true => UNIX_EPOCH.checked_add(seconds)?,
+        false => UNIX_EPOCH.checked_sub(seconds)?,
+    };
+    time.checked_add(Duration::from_nanos(metadata.ctime_nsec() as u64))
+}
+
+#[cfg(not(unix))]
+fn change_time(_metadata: &Metadata) -> Option<SystemTime> {
+    None
+}
+
+impl FromStr for TimeField {
+    type Err = String;
+
+    fn from_str(field: &str) -> Result<Self, Self::Err> {
+        match field {
+            "mtime" | "modified" => Ok(TimeField::Modified),
+            "atime" | "accessed" => Ok(TimeField::Accessed),
+            "ctime" | "changed" => Ok(TimeField::Changed),
+            "btime" | "created" | "birth" => Ok(TimeField::Created),
+            _ => Err(format!("unknown time field: {}", field)),
+        }
+    }
+}
+
+impl fmt::Display for TimeField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeField::Modified => write!(f, "mtime"),
+            TimeField::Accessed => write!(f, "atime"),
+            TimeField::Changed => write!(f, "ctime"),
+            TimeField::Created => write!(f, "btime"),
+        }
+    }
+}
+
+// Filter by one of object times
+// Objects which don't have the compared time (e.g. no creation time) never match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeFilter {
+    // Time is after given one
+    Newer(TimeField, SystemTime),
+    // Time is before given one
+    Older(TimeField, SystemTime),
+}
+
+impl TimeFilter {
+    // Whether object with given metadata satisfies the filter
+    pub fn is_match(&self, metadata: &Metadata) -> bool {
+        match self {
+            TimeFilter::Newer(field, time) => field.get(metadata).is_some_and(|value| value > *time),
+            TimeFilter::Older(field, time) => field.get(metadata).is_some_and(|value| value < *time),
+        }
+    }
+}
+
+// This function parses point in time, either absolute or relative to the current time
+//     2026-01-01, 2026-01-01 12:30, 2026-01-01T12:30:15  date and time in UTC
+//     @1767225600                                      Unix timestamp
+//     2d, 6w, 1h30min                                  duration ago from now
+// Duration units: s (sec, second), m (min, minute), h (hour), d (day), w (week),
+// mo (month, 30 days) and y (year, 365 days), plural forms are accepted too
+pub fn parse_time(text: &str) -> Result<SystemTime, String> {
+    let text = text.trim();
+
+    if let Some(timestamp) = text.strip_prefix('@') {
+        let seconds: u64 = timestamp.parse().map_err(|_| format!("invalid timestamp: {}", text))?;
+        return Ok(UNIX_EPOCH + Duration::from_secs(seconds));
+    }
+    if let Some(time) = parse_date_time(text) {
+        return time;
+    }
+
+    let duration = parse_duration(text)?;
+    SystemTime::now()
+        .checked_sub(duration)
+        .ok_or_else(|| format!("duration is too long: {}", text))
+}
+
+// This function parses duration like "2d" or "1h30min"
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid time or duration: {}", text);
+    let mut rest = text.trim();
+    let mut seconds: u64 = 0;
+
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+
+    while !rest.is_empty() {
+        let number_end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let unit_end = rest[number_end..]
+            .find(|c: char| c.is_ascii_digit())
+            .map_or(rest.len(), |end| number_end + end);
+
+        let number: u64 = rest[..number_end].parse().map_err(|_| invalid())?;
+        let unit = match rest[number_end..unit_end].trim() {
+            "s" | "sec" | "secs" | "second" | "seconds" => 1,
+            "m" | "min" | "mins" | "minute" | "minutes" => MINUTE,
+            "h" | "hour" | "hours" => HOUR,
+            "d" | "day" | "days" => DAY,
+            "w" | "week" | "weeks" => 7 * DAY,
+            "mo" | "month" | "months" => 30 * DAY,
+            "y" | "year" | "years" => 365 * DAY,
+            _ => return Err(invalid()),
+        };
+
+        seconds = number
+            .checked_mul(unit)
+            .and_then(|part| seconds.checked_add(part))
+            .ok_or_else(invalid)?;
+        rest = &rest[unit_end..];
+    }
+
+    Ok(Duration::from_secs(seconds))
+}
+
+// This function parses "YYYY-MM-DD" with optional "HH:MM" or "HH:MM:SS" separated by space or "T"
+// Function returns None if text doesn't look like a date at all
+fn parse_date_time(text: &str) -> Option<Result<SystemTime, String>> {
+    let (date, time) = match text.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time.trim())),
+        None => (text, None),
+    };
+
+    let date: Vec<&str> = date.split('-').collect();
+    let is_number = |part: &&str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    if date.len() != 3 || !date.iter().all(is_number) {
+        return None;
+    }
+
+    Some(date_time_to_system_time(&date, time).map_err(|_| format!("invalid date: {}", text)))
+}
+
+// This function converts date parts (year, month, day) and optional time to point in time
+fn date_time_to_system_time(date: &[&str], time: Option<&str>) -> Result<SystemTime, ()> {
+    let year: i64 = date[0].parse().map_err(drop)?;
+    let month: u32 = date[1].parse().map_err(drop)?;
+    let day: u32 = date[2].parse().map_err(drop)?;
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return Err(());
+    }
+
+    let mut seconds_of_day = 0;
+    if let Some(time) = time {
+        let parts: Vec<u64> = time
+            .split(':')
+            .map(|part| part.parse().map_err(drop))
+            .collect::<Result<_, _>>()?;
+        let (hours, minutes, seconds) = match parts[..] {
+            [hours, minutes] => (hours, minutes, 0),
+            [hours, minutes, seconds] => (hours, minutes, seconds),
+            _ => return Err(()),
+        };
+        if hours > 23 || minutes > 59 || seconds > 59 {
+            return Err(());
+        }
+        seconds_of_day = hours * HOUR + minutes * MINUTE + seconds;
+    }
+
+    let seconds = days_from_civil(year, month, day) * DAY as i64 + seconds_of_day as i64;
+    let time = match seconds >= 0 {
+        true => UNIX_EPOCH.checked_add(Duration::from_secs(seconds as u64)),
+        false => UNIX_EPOCH.checked_sub(Duration::from_secs(seconds.unsigned_abs())),
+    };
+    time.ok_or(())
+}
+
+// Amount of days since 1970-01-01 for given date of proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestamp(text: &str) -> u64 {
+        parse_time(text).unwrap().duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    #[test]
+    fn civil_days() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(days_from_civil(2024, 2, 29), 19782);
+        assert_eq!(days_from_civil(2026, 1, 1), 20454);
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(timestamp("2026-01-01"), 1767225600);
+        assert_eq!(timestamp("2026-01-01 12:30"), 1767225600 + 12 * HOUR + 30 * MINUTE);
+        assert_eq!(timestamp("2026-01-01T12:30:15"), 1767225600 + 12 * HOUR + 30 * MINUTE + 15);
+        assert_eq!(timestamp("@1767225600"), 1767225600);
+        assert!(parse_time("1969-12-31").unwrap() < UNIX_EPOCH);
+    }
+
+    #[test]
+    fn invalid_dates() {
+        assert!(parse_time("2025-02-29").is_err());
+        assert!(parse_time("2026-13-01").is_err());
+        assert!(parse_time("2026-01-00").is_err());
+        assert!(parse_time("2026-01-01 24:00").is_err());
+        assert!(parse_time("2026-01-01 12").is_err());
+        assert!(parse_time("@-5").is_err());
+        assert!(parse_time("").is_err());
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("2d"), Ok(Duration::from_secs(2 * DAY)));
+        assert_eq!(parse_duration("1h30min"), Ok(Duration::from_secs(HOUR + 30 * MINUTE)));
+        assert_eq!(parse_duration("6 weeks"), Ok(Duration::from_secs(42 * DAY)));
+        assert_eq!(parse_duration("1mo"), Ok(Duration::from_secs(30 * DAY)));
+        assert_eq!(parse_duration("1y2s"), Ok(Duration::from_secs(365 * DAY + 2)));
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("2x").is_err());
+        assert!(parse_duration("99999999999999999999y").is_err());
+    }
+
+    #[test]
+    fn relative_times_are_in_the_past() {
+        let before = SystemTime::now() - Duration::from_secs(2 * DAY);
+        let parsed = parse_time("2d").unwrap();
+        let after = SystemTime::now() - Duration::from_secs(2 * DAY);
+        assert!(before <= parsed && parsed <= after);
+    }
+}
@@ -0,0 +1,180 @@
+use std::fs::{DirEntry, ReadDir};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::empty::EmptyDirs;
+use crate::error::WalkError;
+use crate::ignore::Ignore;
+use crate::searcher::{Match, Searcher};
+
+// Dir waiting in the work queue to be read
+pub(crate) struct Dir {
+    pub path: PathBuf,
+    // Amount of dirs between the search dir and this one, 0 for the search dir itself
+    pub depth: usize,
+    // Ignore rules applied to the dir contents, None if ignore files are not used
+    pub ignore: Option<Arc<Ignore>>,
+}
+
+impl Dir {
+    // This function creates the search root dir
+    pub(crate) fn root(searcher: &Searcher) -> Self {
+        let path = searcher.options.root.clone();
+        let ignore = (!searcher.options.no_ignore).then(|| Ignore::for_root(&path));
+
+        Dir { path, depth: 0, ignore }
+    }
+}
+
+// This function opens dir for reading
+pub(crate) fn open_dir(dir: &Dir) -> Result<ReadDir, WalkError> {
+    std::fs::read_dir(&dir.path).map_err(|err| WalkError::new(dir.path.clone(), err))
+}
+
+// This function processes one entry of a dir being read
+// Function returns the dir to go through later (if entry is a dir) and the match
+// (if entry satisfies search options)
+pub(crate) fn visit_entry(
+    searcher: &Searcher,
+    empty_dirs: &EmptyDirs,
+    dir: &Dir,
+    entry: DirEntry,
+) -> (Option<Dir>, Option<Match>) {
+    let options = &searcher.options;
+    // Depth of entries in the search dir is 1
+    let depth = dir.depth + 1;
+    let path = entry.path();
+    // Type of entry is usually known from the dir listing, only symlinks need an additional
+    // stat to find out whether they point to dirs
+    let is_symlink = entry.file_type().is_ok_and(|file_type| file_type.is_symlink());
+    let is_dir = match entry.file_type() {
+        Ok(file_type) if !is_symlink => file_type.is_dir(),
+        _ => path.is_dir(),
+    };
+
+    if searcher.excludes.is_excluded(&path, is_dir) {
+        // Excluded dir is neither found nor searched in
+        return (None, None);
+    }
+    if dir.ignore.as_ref().is_some_and(|ignore| ignore.is_ignored(&path, is_dir)) {
+        return (None, None);
+    }
+    // Git internals are never searched in when ignore files are used, even with hidden objects,
+    // though .git dirs themselves can still be found
+    let git_dir = dir.ignore.is_some() && is_dir && entry.file_name() == ".git";
+
+    // Hidden objects are skipped unless they are included by options or the searched name
+    // refers to them (e.g. ".env"), hidden dirs are searched in only if paths are matched
+    // (e.g. ".config/nvim" with relative paths), so searching ".git" lists repositories
+    // without going through their contents
+    let hidden = !options.hidden && is_hidden(&entry);
+    if hidden && !searcher.name_matcher.finds_hidden(&entry.file_name()) {
+        return (None, None);
+    }
+    let searched_in = !hidden || searcher.name_matcher.searches_hidden_dir(&entry.file_name());
+
+    // Symlinks to dirs are not followed, otherwise a link to a parent dir
+    // would make the traversal endless
+    // Dirs at max depth are not opened, their contents would be too deep anyway
+    let below_max_depth = options.max_depth.is_none_or(|max_depth| depth < max_depth);
+    let subdir = if is_dir
+        && searched_in
+        && !git_dir
+        && below_max_depth
+        && !is_symlink
+    {
+        let ignore = dir.ignore.as_ref().map(|ignore| Ignore::for_dir(ignore, &path));
+        Some(Dir { path: path.clone(), depth, ignore })
+    } else {
+        None
+    };
+
+    // Objects above min depth are not found, but dirs are still searched in
+    // Objects deeper than max depth are not found, this happens only with max depth 0
+    let beyond_max_depth = options.max_depth.is_some_and(|max_depth| depth > max_depth);
+    if depth < options.min_depth || beyond_max_depth {
+        return (subdir, None);
+    }
+
+    (subdir, searcher.find(path, is_dir, depth, empty_dirs))
+}
+
+// Whether dir entry is hidden: its name starts with "." or, on Windows,
+// it has hidden attribute
+fn is_hidden(entry: &DirEntry) -> bool {
+    if entry.file_name().as_encoded_bytes().starts_with(b".") {
+        return true;
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        if let Ok(metadata) = entry.metadata() {
+            return metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0;
+        }
+    }
+
+    false
+}
+
+// Single threaded lazy walker
+// Traversal doesn't use recursion: found dirs are put to the work queue and
+// only one dir is opened at a time, so trees of any depth can be searched
+// without overflowing the stack or running out of file descriptors
+pub(crate) struct Walk<'a> {
+    searcher: &'a Searcher,
+    // Dirs waiting to be read (search root at the beginning)
+    pending: Vec<Dir>,
+    // Dir which is being read now
+    current: Option<(Dir, ReadDir)>,
+    // Results of recursive emptiness checks for dirs the walk hasn't got to yet
+    empty_dirs: EmptyDirs,
+}
+
+impl<'a> Walk<'a> {
+    pub(crate) fn new(searcher: &'a Searcher) -> Self {
+        Walk {
+            searcher,
+            pending: vec![Dir::root(searcher)],
+            current: None,
+            empty_dirs: EmptyDirs::default(),
+        }
+    }
+}
+
+impl Iterator for Walk<'_> {
+    type Item = Result<Match, WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (dir, files) = match &mut self.current {
+                Some(current) => current,
+                None => {
+                    // Current dir is fully read, taking the next one from the queue
+                    let dir = self.pending.pop()?;
+                    match open_dir(&dir) {
+                        Ok(files) => self.current.insert((dir, files)),
+                        Err(err) => return Some(Err(err)),
+                    }
+                }
+            };
+
+            let entry = match files.next() {
+                Some(Ok(entry)) => entry,
+                Some(Err(err)) => return Some(Err(WalkError::new(dir.path.clone(), err))),
+                None => {
+                    self.current = None;
+                    continue;
+                }
+            };
+
+            let (subdir, found) = visit_entry(self.searcher, &self.empty_dirs, dir, entry);
+            self.pending.extend(subdir);
+
+            if let Some(found) = found {
+                return Some(Ok(found));
+            }
+        }
+    }
+}